@@ -0,0 +1,465 @@
+//! Every animal in the Zoo, with its family and emoji.
+//!
+//! Starts out with the built-in names, and fills in the details from fetched profiles.
+
+use std::collections::BTreeSet;
+
+use serenity::utils::MessageBuilder;
+use zoo_api::{ZooProfileAnimal, ZooProfileResponse};
+
+/// Built-in names, until profiles show the real ones.
+const COMMON_ANIMALS: [&str; 50] = [
+    "bat",
+    "bear",
+    "beaver",
+    "beetle",
+    "camel",
+    "cat",
+    "caterpillar",
+    "chick",
+    "chicken",
+    "cow",
+    "crab",
+    "cricket",
+    "crocodile",
+    "dinosaur",
+    "dog",
+    "dove",
+    "duck",
+    "elephant",
+    "fish",
+    "fly",
+    "fox",
+    "frog",
+    "giraffe",
+    "gorilla",
+    "hamster",
+    "hedgehog",
+    "hippo",
+    "horse",
+    "koala",
+    "leopard",
+    "lizard",
+    "mouse",
+    "ox",
+    "parrot",
+    "penguin",
+    "pig",
+    "rabbit",
+    "seal",
+    "sheep",
+    "shrimp",
+    "skunk",
+    "sloth",
+    "snail",
+    "snowman",
+    "spider",
+    "squid",
+    "turkey",
+    "whale",
+    "worm",
+    "zebra",
+];
+
+const RARE_ANIMALS: [&str; 50] = [
+    "bactrian camel",
+    "badger",
+    "bee",
+    "bird",
+    "bison",
+    "boar",
+    "bunny",
+    "butterfly",
+    "chipmunk",
+    "cockroach",
+    "deer",
+    "dodo",
+    "dolphin",
+    "dragon",
+    "eagle",
+    "flamingo",
+    "goat",
+    "kangaroo",
+    "ladybug",
+    "lion",
+    "llama",
+    "lobster",
+    "mammoth",
+    "monkey",
+    "mosquito",
+    "octopus",
+    "orangutan",
+    "otter",
+    "owl",
+    "panda",
+    "peacock",
+    "polar bear",
+    "poodle",
+    "pufferfish",
+    "raccoon",
+    "ram",
+    "rat",
+    "rhino",
+    "rooster",
+    "scorpion",
+    "shark",
+    "snake",
+    "snowier man",
+    "swan",
+    "t-rex",
+    "tiger",
+    "tropical fish",
+    "turtle",
+    "unicorn",
+    "wolf",
+];
+
+/// Most choices Discord shows for autocomplete.
+const MAX_CHOICES: usize = 25;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Animal {
+    pub name: String,
+    pub rare: bool,
+    /// Empty until seen in a profile
+    pub family: String,
+    /// Empty until seen in a profile
+    pub emoji: String,
+}
+
+impl Animal {
+    fn builtin(name: &str, rare: bool) -> Self {
+        Animal { name: title_case(name), rare, family: String::new(), emoji: String::new() }
+    }
+
+    /// Name with the emoji in front, if known.
+    pub fn label(&self) -> String {
+        if self.emoji.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} {}", self.emoji, self.name)
+        }
+    }
+}
+
+pub struct Catalog {
+    animals: Vec<Animal>,
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        let common = COMMON_ANIMALS.iter().map(|name| Animal::builtin(name, false));
+        let rare = RARE_ANIMALS.iter().map(|name| Animal::builtin(name, true));
+        Catalog { animals: common.chain(rare).collect() }
+    }
+}
+
+impl Catalog {
+    pub fn len(&self) -> usize { self.animals.len() }
+
+    /// Look up an animal, ignoring case, spaces and punctuation.
+    pub fn get(&self, name: &str) -> Option<&Animal> {
+        let key = normalize(name);
+        self.animals.iter().find(|animal| normalize(&animal.name) == key)
+    }
+
+    /// Fill in names, families and emoji from profiles. Returns the number of changed animals.
+    pub fn update<'a>(
+        &mut self,
+        profiles: impl IntoIterator<Item = &'a ZooProfileResponse>,
+    ) -> usize {
+        let mut changed = 0;
+        for seen in profiles.into_iter().flat_map(|profile| &profile.animals) {
+            if seen.name.trim().is_empty() {
+                continue;
+            }
+            let animal = Animal {
+                name: seen.name.clone(),
+                rare: seen.rare,
+                family: seen.family.clone(),
+                emoji: seen.emoji.clone(),
+            };
+            let key = normalize(&seen.name);
+            match self.animals.iter_mut().find(|animal| normalize(&animal.name) == key) {
+                Some(existing) if *existing == animal => {}
+                Some(existing) => {
+                    *existing = animal;
+                    changed += 1;
+                }
+                None => {
+                    self.animals.push(animal);
+                    changed += 1;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Animals for autocompleting `partial`: matching the start of the name or family first, then
+    /// anywhere in the name, then similar names.
+    pub fn search(&self, partial: &str) -> Vec<&Animal> {
+        let key = normalize(partial);
+        if key.is_empty() {
+            let mut animals = self.animals.iter().collect::<Vec<_>>();
+            animals.sort_by(|a, b| a.name.cmp(&b.name));
+            animals.truncate(MAX_CHOICES);
+            return animals;
+        }
+        let mut scored = self
+            .animals
+            .iter()
+            .filter_map(|animal| {
+                let name = normalize(&animal.name);
+                let score = if name.starts_with(&key) || normalize(&animal.family).starts_with(&key)
+                {
+                    0
+                } else if name.contains(&key) {
+                    1
+                } else if strsim::jaro_winkler(&key, &name) >= 0.85 {
+                    2
+                } else {
+                    return None;
+                };
+                Some((score, animal))
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by(|(a_score, a), (b_score, b)| a_score.cmp(b_score).then(a.name.cmp(&b.name)));
+        scored.into_iter().map(|(_, animal)| animal).take(MAX_CHOICES).collect()
+    }
+
+    /// Animals with a name close to a misspelled `name`, closest first.
+    pub fn suggest(&self, name: &str, limit: usize) -> Vec<&Animal> {
+        closest(&self.animals, |animal| &animal.name, name, limit)
+    }
+
+    /// Animals the profile doesn't have any of.
+    pub fn missing(&self, profile: &ZooProfileResponse) -> Vec<&Animal> {
+        let owned = profile
+            .animals
+            .iter()
+            .filter(|animal| animal.amount > 0)
+            .map(|animal| normalize(&animal.name))
+            .collect::<BTreeSet<_>>();
+        self.animals.iter().filter(|animal| !owned.contains(&normalize(&animal.name))).collect()
+    }
+
+    /// Known families, sorted by name.
+    pub fn families(&self) -> Vec<&str> {
+        let families = self
+            .animals
+            .iter()
+            .filter(|animal| !animal.family.is_empty())
+            .map(|animal| animal.family.as_str())
+            .collect::<BTreeSet<_>>();
+        families.into_iter().collect()
+    }
+
+    /// Look up a family, ignoring case, spaces and punctuation.
+    pub fn get_family(&self, name: &str) -> Option<&str> {
+        let key = normalize(name);
+        self.families().into_iter().find(|family| normalize(family) == key)
+    }
+
+    /// Families for autocompleting `partial`: matching the start of the name first, then anywhere
+    /// in the name.
+    pub fn search_families(&self, partial: &str) -> Vec<&str> {
+        let key = normalize(partial);
+        let mut families = self
+            .families()
+            .into_iter()
+            .filter(|family| normalize(family).contains(&key))
+            .collect::<Vec<_>>();
+        families.sort_by_key(|family| !normalize(family).starts_with(&key));
+        families.truncate(MAX_CHOICES);
+        families
+    }
+
+    /// Families with a name close to a misspelled `name`, closest first.
+    pub fn suggest_family(&self, name: &str, limit: usize) -> Vec<&str> {
+        closest(self.families(), |family| family, name, limit)
+    }
+}
+
+/// Animals searched for, all given filters have to match.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AnimalQuery {
+    /// Any of these animals, or any animal if empty
+    pub names: Vec<String>,
+    pub family: Option<String>,
+    pub rare: Option<bool>,
+}
+
+impl AnimalQuery {
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.family.is_none() && self.rare.is_none()
+    }
+
+    /// Whether the profile has at least one of a matching animal.
+    pub fn matches(&self, animal: &ZooProfileAnimal) -> bool {
+        animal.amount > 0
+            && (self.names.is_empty()
+                || self.names.iter().any(|name| animal.name.eq_ignore_ascii_case(name)))
+            && self.family.as_ref().is_none_or(|family| animal.family.eq_ignore_ascii_case(family))
+            && self.rare.is_none_or(|rare| animal.rare == rare)
+    }
+
+    /// Markdown description, e.g. "rare animals of the **Cat** family" or "**Bat** or **Owl**".
+    pub fn describe(&self) -> String {
+        let mut message = MessageBuilder::new();
+        match self.rare {
+            Some(true) => message.push("rare "),
+            Some(false) => message.push("common "),
+            None => &mut message,
+        };
+        if self.names.is_empty() {
+            message.push("animals");
+        }
+        for (i, name) in self.names.iter().enumerate() {
+            if i > 0 {
+                message.push(if i + 1 == self.names.len() { " or " } else { ", " });
+            }
+            message.push_bold_safe(name);
+        }
+        if let Some(family) = &self.family {
+            message.push(" of the ").push_bold_safe(family).push(" family");
+        }
+        message.build()
+    }
+}
+
+/// Items with a name close to a misspelled `name`, closest first.
+fn closest<'a, T: ?Sized>(
+    items: impl IntoIterator<Item = &'a T>,
+    name_of: impl Fn(&T) -> &str,
+    name: &str,
+    limit: usize,
+) -> Vec<&'a T> {
+    let key = normalize(name);
+    let mut scored = items
+        .into_iter()
+        .map(|item| (strsim::damerau_levenshtein(&key, &normalize(name_of(item))), item))
+        .filter(|(distance, _)| *distance <= max_typos(&key))
+        .collect::<Vec<_>>();
+    scored.sort_by(|(a_distance, a), (b_distance, b)| {
+        a_distance.cmp(b_distance).then(name_of(a).cmp(name_of(b)))
+    });
+    scored.into_iter().map(|(_, item)| item).take(limit).collect()
+}
+
+fn normalize(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Typos allowed in a name of this length.
+fn max_typos(key: &str) -> usize { (key.chars().count() / 3).max(1) }
+
+fn title_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut capitalize = true;
+    for c in name.chars() {
+        if capitalize {
+            result.extend(c.to_uppercase());
+        } else {
+            result.push(c);
+        }
+        capitalize = c == ' ' || c == '-';
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockZoo;
+
+    fn names(animals: Vec<&Animal>) -> Vec<&str> {
+        animals.into_iter().map(|animal| animal.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_builtin() {
+        let catalog = Catalog::default();
+        assert_eq!(catalog.len(), 100);
+        assert_eq!(catalog.get("polar bear").unwrap().name, "Polar Bear");
+        assert_eq!(catalog.get("PolarBear").unwrap().name, "Polar Bear");
+        assert_eq!(catalog.get("trex").unwrap().name, "T-Rex");
+        assert!(catalog.get("tiger").unwrap().rare);
+        assert!(!catalog.get("cat").unwrap().rare);
+        assert!(catalog.get("catt").is_none());
+    }
+
+    #[test]
+    fn test_update() {
+        let profile: ZooProfileResponse =
+            serde_json::from_str(include_str!("../fixtures/profiles/100_main.json")).unwrap();
+        let mut catalog = Catalog::default();
+        assert_eq!(catalog.update([&profile]), 3);
+        assert_eq!(catalog.update([&profile]), 0);
+        let cat = catalog.get("cat").unwrap();
+        assert_eq!(cat.label(), "🐱 Cat");
+        assert_eq!(cat.family, "Cat");
+        assert_eq!(catalog.get("lion").unwrap().family, "Cat");
+        assert_eq!(catalog.families(), ["Cat", "Dog"]);
+        assert_eq!(catalog.get_family("cat"), Some("Cat"));
+        assert_eq!(catalog.search_families("o"), ["Dog"]);
+        assert_eq!(catalog.suggest_family("dgo", 3), ["Dog"]);
+        let missing = catalog.missing(&profile);
+        assert_eq!(missing.len(), 97);
+        assert!(!names(missing).contains(&"Lion"));
+    }
+
+    #[test]
+    fn test_query() {
+        let profile: ZooProfileResponse =
+            serde_json::from_str(include_str!("../fixtures/profiles/100_alt.json")).unwrap();
+        let (cat, dog) = (&profile.animals[0], &profile.animals[1]);
+        let query =
+            AnimalQuery { names: vec!["cat".to_string(), "Dog".to_string()], ..Default::default() };
+        assert!(query.matches(cat));
+        // None left
+        assert!(!query.matches(dog));
+        assert!(!AnimalQuery {
+            family: Some("Cat".to_string()),
+            rare: Some(true),
+            ..query.clone()
+        }
+        .matches(cat));
+        assert_eq!(query.describe(), "**cat** or **Dog**");
+
+        let query =
+            AnimalQuery { family: Some("Cat".to_string()), rare: Some(true), ..Default::default() };
+        assert_eq!(query.describe(), "rare animals of the **Cat** family");
+        assert!(!query.is_empty());
+        assert!(AnimalQuery::default().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_drifted() {
+        let mut profile: serde_json::Value =
+            serde_json::from_str(include_str!("../fixtures/profiles/100_main.json")).unwrap();
+        profile["animals"][0] = "Cat".into();
+        profile["animals"][1]["name"] = 5.into();
+        profile["animals"][2]["name"] = " ".into();
+        let mock = MockZoo::start().await;
+        mock.route("/api/profile/100_main", 200, &profile.to_string());
+        let profile = mock.client().fetch_profile(100, Some("main")).await.unwrap();
+        let mut catalog = Catalog::default();
+        assert_eq!(catalog.update([profile.as_ref()]), 0);
+        assert_eq!(catalog.len(), 100);
+        assert!(catalog.search("").iter().all(|animal| !animal.name.is_empty()));
+    }
+
+    #[test]
+    fn test_search() {
+        let catalog = Catalog::default();
+        assert_eq!(names(catalog.search("")).len(), MAX_CHOICES);
+        assert_eq!(names(catalog.search("pol"))[0], "Polar Bear");
+        assert_eq!(names(catalog.search("fish")), ["Fish", "Pufferfish", "Tropical Fish"]);
+        assert!(names(catalog.search("elefant")).contains(&"Elephant"));
+
+        assert_eq!(names(catalog.suggest("girafe", 3)), ["Giraffe"]);
+        assert_eq!(names(catalog.suggest("cst", 3)), ["Cat"]);
+        assert_eq!(names(catalog.suggest("polr ber", 3)), ["Polar Bear"]);
+        assert!(catalog.suggest("xyzzy", 3).is_empty());
+    }
+}
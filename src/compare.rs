@@ -0,0 +1,101 @@
+//! `/compare`: the stats of two zoos side by side, and the animals only one of them has.
+
+use anyhow::Result;
+use poise::serenity_prelude::MessageBuilder;
+use zoo_api::ZooProfileResponse;
+
+use crate::profile_link;
+
+/// Most animals listed per zoo by `/compare`.
+const COMPARE_MAX_ANIMALS: usize = 15;
+
+/// Format the stats of two profiles side by side, and the animals only one of them has.
+pub fn compare_profiles(a: &ZooProfileResponse, b: &ZooProfileResponse) -> Result<String> {
+    let a_link = profile_link(&a.name, a.user_id.parse()?, Some(&a.profile_id));
+    let b_link = profile_link(&b.name, b.user_id.parse()?, Some(&b.profile_id));
+    let mut message = MessageBuilder::new();
+    message.push(&a_link).push(" vs ").push_line(&b_link);
+
+    let rows = [
+        ("Score", a.score as f64, b.score as f64),
+        ("Completion", a.completion as f64, b.completion as f64),
+        ("Unique animals", a.unique_animals.total as f64, b.unique_animals.total as f64),
+        (
+            "Total animals",
+            (a.total_animals.common + a.total_animals.rare) as f64,
+            (b.total_animals.common + b.total_animals.rare) as f64,
+        ),
+        ("Relics", a.relics.len() as f64, b.relics.len() as f64),
+        ("Cosmetics", a.total_cosmetics as f64, b.total_cosmetics as f64),
+        ("Leader XP", a.total_leader_xp as f64, b.total_leader_xp as f64),
+        ("Goals complete", a.goals_complete as f64, b.goals_complete as f64),
+        ("Goal tiers", a.goal_tiers as f64, b.goal_tiers as f64),
+    ];
+    for (name, a_value, b_value) in rows {
+        let format = |value: f64| {
+            if name == "Completion" {
+                format!("{:.1}%", value)
+            } else {
+                value.to_string()
+            }
+        };
+        message.push_bold(name).push(": ").push(format(a_value));
+        if a_value > b_value {
+            message.push(" 🏆");
+        }
+        message.push(" vs ").push(format(b_value));
+        if b_value > a_value {
+            message.push(" 🏆");
+        }
+        message.push_line("");
+    }
+
+    for (link, profile, other) in [(&a_link, a, b), (&b_link, b, a)] {
+        let owned = |profile: &ZooProfileResponse, name: &str| {
+            profile.animals.iter().any(|animal| animal.amount > 0 && animal.name == name)
+        };
+        let only = profile
+            .animals
+            .iter()
+            .filter(|animal| animal.amount > 0 && !owned(other, &animal.name))
+            .collect::<Vec<_>>();
+        message.push_line("").push("Only in ").push(link.as_str()).push(": ");
+        if only.is_empty() {
+            message.push("nothing");
+        }
+        for (i, animal) in only.iter().take(COMPARE_MAX_ANIMALS).enumerate() {
+            if i > 0 {
+                message.push(", ");
+            }
+            if !animal.emoji.is_empty() {
+                message.push(format!("{} ", animal.emoji));
+            }
+            message.push_safe(&animal.name);
+        }
+        if only.len() > COMPARE_MAX_ANIMALS {
+            message.push(format!(" and {} more", only.len() - COMPARE_MAX_ANIMALS));
+        }
+    }
+    Ok(message.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_profiles() {
+        let a: ZooProfileResponse =
+            serde_json::from_str(include_str!("../fixtures/profiles/100_main.json")).unwrap();
+        let b: ZooProfileResponse =
+            serde_json::from_str(include_str!("../fixtures/profiles/500.json")).unwrap();
+        let message = compare_profiles(&a, &b).unwrap();
+        let lines = message.lines().collect::<Vec<_>>();
+        assert!(lines[0].contains(" vs [**Single Zoo**]"), "{}", lines[0]);
+        assert_eq!(lines[1], "**Score**: 1234 vs 1234");
+        assert_eq!(lines[2], "**Completion**: 12.5% vs 12.5%");
+        assert_eq!(lines[3], "**Unique animals**: 3 🏆 vs 1");
+        assert!(lines[11].ends_with(": 🦁 Lion, 🐶 Dog"), "{}", lines[11]);
+        assert!(lines[12].ends_with(": nothing"), "{}", lines[12]);
+    }
+}
@@ -0,0 +1,248 @@
+//! `/goals` and goal alerts: progress of goals to their next tier.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use poise::serenity_prelude::{ChannelId, MessageBuilder, UserId};
+use zoo_api::{ZooProfileGoal, ZooProfileResponse};
+
+use crate::{profile_link, Config, Notification};
+
+/// Progress of a goal at the last check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct GoalSnapshot {
+    tier: u32,
+    percent: u32,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct GoalAlert {
+    pub channel_id: ChannelId,
+    /// Percent of the next tier to alert at
+    pub threshold: u32,
+    /// Progress by profile ID and goal name, e.g. `main/Rescuer`
+    #[serde(default)]
+    pub snapshot: BTreeMap<String, GoalSnapshot>,
+}
+
+impl GoalAlert {
+    /// Update the snapshot with the goals of the profiles, and return the goals that reached the
+    /// threshold since the last check. Goals seen for the first time don't alert.
+    fn update<'a>(
+        &mut self,
+        profiles: &'a [Arc<ZooProfileResponse>],
+    ) -> Vec<(&'a ZooProfileResponse, &'a ZooProfileGoal)> {
+        let mut reached = vec![];
+        for profile in profiles {
+            for goal in &profile.goals {
+                let current = GoalSnapshot { tier: goal.tier_number, percent: goal_percent(goal) };
+                let key = format!("{}/{}", profile.profile_id, goal.name);
+                if let Some(previous) = self.snapshot.insert(key, current) {
+                    // Progress starts over with every tier
+                    let before = if previous.tier == current.tier { previous.percent } else { 0 };
+                    if !goal.complete
+                        && before < self.threshold
+                        && current.percent >= self.threshold
+                    {
+                        reached.push((profile.as_ref(), goal));
+                    }
+                }
+            }
+        }
+        reached
+    }
+}
+
+/// Progress to the next tier of a goal, in percent.
+fn goal_percent(goal: &ZooProfileGoal) -> u32 {
+    if goal.complete || goal.target == 0 {
+        return 100;
+    }
+    (goal.count.min(goal.target) as u64 * 100 / goal.target as u64) as u32
+}
+
+/// Most goals listed by `/goals`.
+const GOALS_MAX: usize = 15;
+
+pub fn format_goals(user_id: UserId, profiles: &[Arc<ZooProfileResponse>]) -> String {
+    let mut goals = profiles
+        .iter()
+        .flat_map(|profile| profile.goals.iter().map(move |goal| (profile, goal)))
+        .filter(|(_, goal)| !goal.complete)
+        .collect::<Vec<_>>();
+    goals.sort_by(|(_, a), (_, b)| goal_percent(b).cmp(&goal_percent(a)).then(a.name.cmp(&b.name)));
+    let complete =
+        profiles.iter().flat_map(|profile| &profile.goals).filter(|goal| goal.complete).count();
+
+    let mut message = MessageBuilder::new();
+    if goals.is_empty() {
+        message.push("No goals in progress for ").user(user_id).push_line(".");
+    } else {
+        message
+            .push("Goals of ")
+            .user(user_id)
+            .push_line(", the closest to their next tier first:");
+    }
+    for (profile, goal) in goals.iter().take(GOALS_MAX) {
+        message.push("- ");
+        if !goal.emoji.is_empty() {
+            message.push(format!("{} ", goal.emoji));
+        }
+        message.push_bold_safe(&goal.name);
+        if !goal.tier.is_empty() {
+            message.push(" (").push_safe(&goal.tier).push(")");
+        }
+        message.push(format!(": {}/{} ({}%)", goal.count, goal.target, goal_percent(goal)));
+        if profiles.len() > 1 {
+            message.push(" in ").push(profile_link(
+                &profile.name,
+                user_id,
+                Some(&profile.profile_id),
+            ));
+        }
+        message.push_line("");
+    }
+    if goals.len() > GOALS_MAX {
+        message.push_line(format!("... and {} more", goals.len() - GOALS_MAX));
+    }
+    if complete > 0 {
+        message.push_italic_line(format!("{} goals complete", complete));
+    }
+    message.build()
+}
+
+/// Update the goal snapshots of users with alerts, and build the alerts. Also returns whether the
+/// config changed.
+pub fn collect_goal_alerts(
+    config: &mut Config,
+    profiles: &BTreeMap<UserId, Vec<Arc<ZooProfileResponse>>>,
+) -> (Vec<Notification>, bool) {
+    let mut notifications = vec![];
+    let mut changed = false;
+    for (user_id, alert) in &mut config.goal_alerts {
+        let Some(profiles) = profiles.get(user_id) else {
+            continue;
+        };
+        let before = alert.snapshot.clone();
+        let reached = alert.update(profiles);
+        changed |= alert.snapshot != before;
+        if config.disabled_users.contains(user_id) {
+            continue;
+        }
+        for (profile, goal) in reached {
+            let mut message = MessageBuilder::new();
+            message.user(*user_id).push(" ");
+            if !goal.emoji.is_empty() {
+                message.push(format!("{} ", goal.emoji));
+            }
+            message
+                .push_bold_safe(&goal.name)
+                .push(format!(
+                    " is {}% of the way to the next tier ({}/{}) in ",
+                    goal_percent(goal),
+                    goal.count,
+                    goal.target
+                ))
+                .push(profile_link(&profile.name, *user_id, Some(&profile.profile_id)));
+            notifications.push(Notification {
+                channel_id: alert.channel_id,
+                user_id: *user_id,
+                content: message.build(),
+            });
+        }
+    }
+    (notifications, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(name: &str, tier_number: u32, count: u32, target: u32) -> ZooProfileGoal {
+        ZooProfileGoal {
+            name: name.to_string(),
+            tier: format!("Tier {}", tier_number),
+            tier_number,
+            target,
+            count,
+            ..Default::default()
+        }
+    }
+
+    fn goal_profile(profile_id: &str, goals: Vec<ZooProfileGoal>) -> Arc<ZooProfileResponse> {
+        Arc::new(ZooProfileResponse {
+            user_id: "100".to_string(),
+            profile_id: profile_id.to_string(),
+            name: format!("{} zoo", profile_id),
+            goals,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_format_goals() {
+        let profiles = [
+            goal_profile("main", vec![goal("Rescuer", 1, 42, 100), goal("Fisher", 2, 9, 10)]),
+            goal_profile("alt", vec![goal("Rescuer", 1, 80, 100), ZooProfileGoal {
+                complete: true,
+                ..goal("Done", 3, 5, 5)
+            }]),
+        ];
+        let message = format_goals(UserId::new(100), &profiles);
+        let lines = message.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "Goals of <@100>, the closest to their next tier first:");
+        assert!(
+            lines[1].starts_with("- **Fisher** (Tier 2): 9/10 (90%) in [**main zoo**]"),
+            "{}",
+            lines[1]
+        );
+        assert!(
+            lines[2].starts_with("- **Rescuer** (Tier 1): 80/100 (80%) in [**alt zoo**]"),
+            "{}",
+            lines[2]
+        );
+        assert_eq!(lines[4], "_1 goals complete_");
+        let message = format_goals(UserId::new(100), &[]);
+        assert_eq!(message, "No goals in progress for <@100>.\n");
+    }
+
+    #[test]
+    fn test_collect_goal_alerts() {
+        let user_id = UserId::new(100);
+        let alert =
+            GoalAlert { channel_id: ChannelId::new(10), threshold: 90, snapshot: BTreeMap::new() };
+        let mut config =
+            Config { goal_alerts: BTreeMap::from([(user_id, alert)]), ..Default::default() };
+        let check = |config: &mut Config, goals: Vec<ZooProfileGoal>| {
+            let profiles = BTreeMap::from([(user_id, vec![goal_profile("main", goals)])]);
+            collect_goal_alerts(config, &profiles)
+        };
+        // The first check only takes a snapshot
+        let (notifications, changed) = check(&mut config, vec![goal("Rescuer", 1, 95, 100)]);
+        assert!(notifications.is_empty());
+        assert!(changed);
+        let (notifications, changed) = check(&mut config, vec![goal("Rescuer", 1, 95, 100)]);
+        assert!(notifications.is_empty());
+        assert!(!changed);
+        // Next tier, then crossing the threshold
+        check(&mut config, vec![goal("Rescuer", 2, 100, 200)]);
+        let (notifications, _) = check(&mut config, vec![goal("Rescuer", 2, 185, 200)]);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].channel_id, ChannelId::new(10));
+        assert!(
+            notifications[0].content.starts_with(
+                "<@100> **Rescuer** is 92% of the way to the next tier (185/200) in [**main zoo**]"
+            ),
+            "{}",
+            notifications[0].content
+        );
+        let (notifications, _) = check(&mut config, vec![goal("Rescuer", 2, 190, 200)]);
+        assert!(notifications.is_empty());
+        // Skipping a tier still alerts
+        let (notifications, _) = check(&mut config, vec![goal("Rescuer", 3, 290, 300)]);
+        assert_eq!(notifications.len(), 1);
+        config.disabled_users.insert(user_id);
+        let (notifications, changed) = check(&mut config, vec![goal("Rescuer", 4, 390, 400)]);
+        assert!(notifications.is_empty());
+        assert!(changed);
+    }
+}
@@ -0,0 +1,304 @@
+//! Local index of channel users' profiles, refreshed in the background so lookups don't have to
+//! wait for the Zoo API.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use chrono::TimeDelta;
+use poise::serenity_prelude::{Timestamp, UserId};
+use tokio::{sync::Semaphore, task::JoinSet};
+use tracing::warn;
+use zoo_api::{ZooClient, ZooError, ZooProfileResponse};
+
+/// Why a profile isn't in the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SkipReason {
+    Private,
+    NoZoo,
+    /// API errors and unreadable profiles, retried on the next crawl
+    Unavailable,
+}
+
+impl SkipReason {
+    fn from_error(error: &ZooError) -> Self {
+        match error {
+            ZooError::NoZoo => SkipReason::NoZoo,
+            ZooError::Private => SkipReason::Private,
+            _ => SkipReason::Unavailable,
+        }
+    }
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Private => write!(f, "private"),
+            SkipReason::NoZoo => write!(f, "without a zoo"),
+            SkipReason::Unavailable => write!(f, "unavailable"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IndexEntry {
+    profiles: Vec<Arc<ZooProfileResponse>>,
+    skipped: Vec<SkipReason>,
+    /// IDs of all profiles of the user, to still find the others when the current one fails
+    profile_ids: BTreeSet<String>,
+    updated: Timestamp,
+    /// Refresh on the next crawl, e.g. after the user rescued an animal
+    stale: bool,
+}
+
+impl IndexEntry {
+    fn needs_refresh(&self, now: Timestamp, max_age: TimeDelta) -> bool {
+        self.stale
+            || self.skipped.contains(&SkipReason::Unavailable)
+            || *self.updated + max_age <= *now
+    }
+}
+
+/// Profiles of some users, read from the index.
+#[derive(Debug, Default)]
+pub struct Lookup {
+    pub profiles: Vec<Arc<ZooProfileResponse>>,
+    pub skipped: BTreeMap<SkipReason, usize>,
+    /// When the oldest of the profiles was fetched
+    pub oldest: Option<Timestamp>,
+}
+
+pub struct ProfileIndex {
+    users: RwLock<HashMap<UserId, IndexEntry>>,
+    /// Users fetched at the same time
+    concurrency: Arc<Semaphore>,
+    /// Longest wait for a single profile, including retries
+    fetch_timeout: Duration,
+}
+
+impl ProfileIndex {
+    pub fn new(concurrency: usize, fetch_timeout: Duration) -> Self {
+        ProfileIndex {
+            users: RwLock::new(HashMap::new()),
+            concurrency: Arc::new(Semaphore::new(concurrency.max(1))),
+            fetch_timeout,
+        }
+    }
+
+    /// Users that need to be (re)fetched.
+    pub fn needs_refresh(&self, user_ids: &[UserId], max_age: TimeDelta) -> Vec<UserId> {
+        let users = self.users.read().unwrap();
+        let now = Timestamp::now();
+        user_ids
+            .iter()
+            .filter(|user_id| {
+                users.get(user_id).is_none_or(|entry| entry.needs_refresh(now, max_age))
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Users that were never indexed.
+    pub fn missing(&self, user_ids: &[UserId]) -> Vec<UserId> {
+        let users = self.users.read().unwrap();
+        user_ids.iter().filter(|user_id| !users.contains_key(user_id)).copied().collect()
+    }
+
+    pub fn lookup(&self, user_ids: &[UserId]) -> Lookup {
+        let users = self.users.read().unwrap();
+        let mut lookup = Lookup::default();
+        for entry in user_ids.iter().filter_map(|user_id| users.get(user_id)) {
+            lookup.profiles.extend(entry.profiles.iter().cloned());
+            for reason in &entry.skipped {
+                *lookup.skipped.entry(*reason).or_default() += 1;
+            }
+            if !entry.profiles.is_empty()
+                && lookup.oldest.is_none_or(|oldest| entry.updated < oldest)
+            {
+                lookup.oldest = Some(entry.updated);
+            }
+        }
+        lookup
+    }
+
+    /// Every indexed profile.
+    pub fn all_profiles(&self) -> Vec<Arc<ZooProfileResponse>> {
+        let users = self.users.read().unwrap();
+        users.values().flat_map(|entry| entry.profiles.iter().cloned()).collect()
+    }
+
+    /// Refresh the user on the next crawl.
+    pub fn mark_stale(&self, user_id: UserId) {
+        if let Some(entry) = self.users.write().unwrap().get_mut(&user_id) {
+            entry.stale = true;
+        }
+    }
+
+    /// Forget users that aren't in any channel anymore.
+    pub fn retain(&self, user_ids: &BTreeSet<UserId>) {
+        self.users.write().unwrap().retain(|user_id, _| user_ids.contains(user_id));
+    }
+
+    /// Indexed users and profiles.
+    pub fn len(&self) -> (usize, usize) {
+        let users = self.users.read().unwrap();
+        (users.len(), users.values().map(|entry| entry.profiles.len()).sum())
+    }
+
+    /// Fetch all profiles of the users.
+    pub async fn refresh(self: &Arc<Self>, zoo: &Arc<ZooClient>, user_ids: Vec<UserId>) {
+        let mut tasks = self.spawn_refresh(zoo, user_ids);
+        while tasks.join_next().await.is_some() {}
+    }
+
+    /// Start fetching all profiles of the users in the background. Each task returns its user once
+    /// the index has been updated.
+    pub fn spawn_refresh(
+        self: &Arc<Self>,
+        zoo: &Arc<ZooClient>,
+        user_ids: Vec<UserId>,
+    ) -> JoinSet<UserId> {
+        let mut tasks = JoinSet::new();
+        for user_id in user_ids {
+            let index = self.clone();
+            let zoo = zoo.clone();
+            tasks.spawn(async move {
+                // Shared with every other refresh
+                let _permit = index.concurrency.acquire().await.unwrap();
+                index.refresh_user(&zoo, user_id).await;
+                user_id
+            });
+        }
+        tasks
+    }
+
+    async fn fetch(
+        &self,
+        zoo: &ZooClient,
+        user_id: UserId,
+        profile: Option<&str>,
+    ) -> Result<Arc<ZooProfileResponse>, ZooError> {
+        match tokio::time::timeout(self.fetch_timeout, zoo.fetch_profile(user_id.get(), profile))
+            .await
+        {
+            Ok(result) => result,
+            Err(_) => Err(ZooError::Unavailable("Timed out".to_string())),
+        }
+    }
+
+    async fn refresh_user(&self, zoo: &ZooClient, user_id: UserId) {
+        let updated = Timestamp::now();
+        let mut profiles = vec![];
+        let mut skipped = vec![];
+        let profile_ids = match self.fetch(zoo, user_id, None).await {
+            Ok(current) => {
+                let others = current
+                    .profiles
+                    .iter()
+                    .filter(|profile| **profile != current.profile_id)
+                    .cloned()
+                    .collect::<BTreeSet<_>>();
+                profiles.push(current);
+                others
+            }
+            Err(e) => {
+                let reason = SkipReason::from_error(&e);
+                if reason == SkipReason::Unavailable {
+                    warn!("Failed to index profiles of user ID {}: {:?}", user_id, e);
+                    // Keep the old profiles until the API is back
+                    if let Some(entry) = self.users.write().unwrap().get_mut(&user_id) {
+                        entry.stale = true;
+                        return;
+                    }
+                }
+                // The other profiles may still be public, if they're known from an earlier crawl
+                let known = match reason {
+                    SkipReason::Unavailable => None,
+                    _ => self.users.read().unwrap().get(&user_id).map(|e| e.profile_ids.clone()),
+                };
+                let known = known.unwrap_or_default();
+                if known.is_empty() {
+                    skipped.push(reason);
+                }
+                known
+            }
+        };
+        for profile in &profile_ids {
+            match self.fetch(zoo, user_id, Some(profile)).await {
+                Ok(profile) => profiles.push(profile),
+                Err(e) => {
+                    let reason = SkipReason::from_error(&e);
+                    if reason == SkipReason::Unavailable {
+                        warn!("Failed to index profile {} of user ID {}: {:?}", profile, user_id, e);
+                    }
+                    skipped.push(reason);
+                }
+            }
+        }
+        let profile_ids = profiles
+            .iter()
+            .flat_map(|profile| profile.profiles.iter().chain([&profile.profile_id]))
+            .cloned()
+            .chain(profile_ids)
+            .collect();
+        // Private profiles are only for their owner, not for channel-wide lookups
+        let (private, profiles): (Vec<_>, _) =
+            profiles.into_iter().partition(|profile| profile.private);
+        skipped.extend(private.iter().map(|_| SkipReason::Private));
+        let entry = IndexEntry { profiles, skipped, profile_ids, updated, stale: false };
+        self.users.write().unwrap().insert(user_id, entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockZoo;
+
+    #[tokio::test]
+    async fn test_refresh() {
+        let mock = MockZoo::start().await;
+        let zoo = Arc::new(mock.client());
+        let index = Arc::new(ProfileIndex::new(2, Duration::from_secs(10)));
+        let user_ids = [100, 200, 500].map(UserId::new);
+        let max_age = TimeDelta::minutes(15);
+        assert_eq!(index.needs_refresh(&user_ids, max_age), user_ids);
+        index.refresh(&zoo, user_ids.to_vec()).await;
+        assert_eq!(index.len(), (3, 3));
+        assert!(index.needs_refresh(&user_ids, max_age).is_empty());
+        let lookup = index.lookup(&user_ids[..1]);
+        assert_eq!(lookup.profiles.len(), 2);
+        assert!(lookup.skipped.is_empty());
+        assert_eq!(index.lookup(&user_ids).skipped, BTreeMap::from([(SkipReason::Private, 1)]));
+
+        // Old profiles are kept while the API is down
+        mock.route("/api/profile/100", 503, "Service Unavailable");
+        index.mark_stale(UserId::new(100));
+        assert_eq!(index.needs_refresh(&user_ids, max_age), [UserId::new(100)]);
+        index.refresh(&zoo, vec![UserId::new(100)]).await;
+        assert_eq!(index.lookup(&user_ids[..1]).profiles.len(), 2);
+        assert_eq!(index.needs_refresh(&user_ids, max_age), [UserId::new(100)]);
+
+        // The current profile turned private, the other known one is still public
+        mock.route("/api/profile/100", 403, r#"{"error":"This profile is private"}"#);
+        mock.route("/api/profile/100_main", 403, r#"{"error":"This profile is private"}"#);
+        index.refresh(&zoo, vec![UserId::new(100)]).await;
+        let lookup = index.lookup(&user_ids[..1]);
+        assert_eq!(lookup.profiles.len(), 1);
+        assert_eq!(lookup.profiles[0].profile_id, "alt");
+        assert_eq!(lookup.skipped, BTreeMap::from([(SkipReason::Private, 1)]));
+
+        // Private profiles aren't indexed
+        index.refresh(&zoo, vec![UserId::new(600)]).await;
+        let lookup = index.lookup(&[UserId::new(600)]);
+        assert!(lookup.profiles.is_empty());
+        assert_eq!(lookup.skipped, BTreeMap::from([(SkipReason::Private, 1)]));
+
+        index.retain(&BTreeSet::from([UserId::new(500)]));
+        assert_eq!(index.len(), (1, 1));
+        assert_eq!(index.missing(&user_ids), [UserId::new(100), UserId::new(200)]);
+    }
+}
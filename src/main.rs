@@ -2,8 +2,9 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashSet},
     fmt::Display,
     ops::Sub,
+    path::PathBuf,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant, SystemTime},
 };
 
 use anyhow::{Context as _, Error, Result};
@@ -14,8 +15,9 @@ use poise::{
 use serenity::{
     all::CreateMessage,
     builder::{
-        CreateActionRow, CreateAllowedMentions, CreateButton, CreateEmbed, CreateEmbedAuthor,
-        CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
+        AutocompleteChoice, CreateActionRow, CreateAllowedMentions, CreateButton, CreateEmbed,
+        CreateEmbedAuthor, CreateEmbedFooter, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
     },
     cache::Cache,
     client::{ClientBuilder, Context as SerenityContext, FullEvent},
@@ -30,19 +32,38 @@ use tokio_util::{sync::CancellationToken, task::TaskTracker};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+mod catalog;
+mod compare;
+mod goals;
+mod index;
+mod missing;
+#[cfg(test)]
+mod mock;
 mod parsers;
-mod zoo;
+mod profile;
+mod quest;
+mod trades;
 
+use catalog::{AnimalQuery, Catalog};
+use compare::compare_profiles;
+use goals::{collect_goal_alerts, format_goals, GoalAlert};
+use index::{Lookup, ProfileIndex, SkipReason};
+use missing::missing_animals;
 use parsers::{
-    extract_card_cooldown, extract_mechanic_cooldown, extract_profile_cooldown,
-    extract_quest_cooldown, extract_rescue_cooldown,
+    invoking_user, profile_evidence, EventKind, InvokerSource, ParserRules, ProfileEvidence,
+    ZooMessage,
 };
-use zoo::{fetch_zoo_profile, profile_url, ZooProfileAnimal, ZooProfileResponse};
+use profile::create_profile_message;
+use quest::format_quest_status;
+use trades::format_trades;
+use zoo_api::{profile_url, ZooClient, ZooError, ZooProfileAnimal, ZooProfileResponse};
 
 struct Data {
     start_time: Timestamp,
     config: Arc<RwLock<Config>>,
-    client: reqwest::Client,
+    zoo: Arc<ZooClient>,
+    index: Arc<ProfileIndex>,
+    catalog: Arc<RwLock<Catalog>>,
     current_user: CurrentUser,
     shard: Option<ShardInfo>,
 }
@@ -51,110 +72,6 @@ type FrameworkContext<'a> = poise::FrameworkContext<'a, Data, Error>;
 
 const ZOO_USER_ID: UserId = UserId::new(1008563327380766812);
 
-const ANIMAL_NAMES: [&str; 100] = [
-    "bat",
-    "bear",
-    "beaver",
-    "beetle",
-    "camel",
-    "cat",
-    "caterpillar",
-    "chick",
-    "chicken",
-    "cow",
-    "crab",
-    "cricket",
-    "crocodile",
-    "dinosaur",
-    "dog",
-    "dove",
-    "duck",
-    "elephant",
-    "fish",
-    "fly",
-    "fox",
-    "frog",
-    "giraffe",
-    "gorilla",
-    "hamster",
-    "hedgehog",
-    "hippo",
-    "horse",
-    "koala",
-    "leopard",
-    "lizard",
-    "mouse",
-    "ox",
-    "parrot",
-    "penguin",
-    "pig",
-    "rabbit",
-    "seal",
-    "sheep",
-    "shrimp",
-    "skunk",
-    "sloth",
-    "snail",
-    "snowman",
-    "spider",
-    "squid",
-    "turkey",
-    "whale",
-    "worm",
-    "zebra",
-    // rare animals
-    "bactrian camel",
-    "badger",
-    "bee",
-    "bird",
-    "bison",
-    "boar",
-    "bunny",
-    "butterfly",
-    "chipmunk",
-    "cockroach",
-    "deer",
-    "dodo",
-    "dolphin",
-    "dragon",
-    "eagle",
-    "flamingo",
-    "goat",
-    "kangaroo",
-    "ladybug",
-    "lion",
-    "llama",
-    "lobster",
-    "mammoth",
-    "monkey",
-    "mosquito",
-    "octopus",
-    "orangutan",
-    "otter",
-    "owl",
-    "panda",
-    "peacock",
-    "polar bear",
-    "poodle",
-    "pufferfish",
-    "raccoon",
-    "ram",
-    "rat",
-    "rhino",
-    "rooster",
-    "scorpion",
-    "shark",
-    "snake",
-    "snowier man",
-    "swan",
-    "t-rex",
-    "tiger",
-    "tropical fish",
-    "turtle",
-    "unicorn",
-    "wolf",
-];
-
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 enum CooldownKind {
     #[default]
@@ -166,6 +83,18 @@ enum CooldownKind {
 }
 
 impl CooldownKind {
+    fn from_event(kind: EventKind) -> Option<Self> {
+        match kind {
+            EventKind::Rescue => Some(CooldownKind::Rescue),
+            EventKind::Quest => Some(CooldownKind::Quest),
+            EventKind::Card => Some(CooldownKind::Card),
+            EventKind::Mechanic => Some(CooldownKind::Mechanic),
+            EventKind::Profile => Some(CooldownKind::Profile),
+            // Not tracked
+            EventKind::Relic | EventKind::Curse => None,
+        }
+    }
+
     fn emoji(&self) -> &str {
         match self {
             CooldownKind::Rescue => "🐾",
@@ -189,6 +118,22 @@ impl Display for CooldownKind {
     }
 }
 
+/// How the profile of a cooldown was determined.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+enum ProfileSource {
+    /// Current profile according to the Zoo API
+    #[default]
+    Api,
+    /// Shown in the Zoo message
+    Message,
+    /// Last known active profile
+    Cache,
+    /// The API was unavailable, the profile is filled in later
+    Unresolved,
+    /// The profile couldn't be filled in, kept without it
+    Unresolvable,
+}
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 struct Cooldown {
     #[serde(default)]
@@ -197,7 +142,92 @@ struct Cooldown {
     user_id: UserId,
     profile: String,
     profile_name: String,
+    #[serde(default)]
+    profile_source: ProfileSource,
     timestamp: Timestamp,
+    /// When the Zoo message was sent
+    #[serde(default)]
+    seen: Option<Timestamp>,
+    /// The Zoo message, so its edits don't track the cooldown again
+    #[serde(default)]
+    message_id: Option<MessageId>,
+}
+
+impl Cooldown {
+    fn is_unresolved(&self) -> bool {
+        match self.profile_source {
+            ProfileSource::Unresolved => true,
+            ProfileSource::Unresolvable => false,
+            _ => self.profile_name.is_empty(),
+        }
+    }
+
+    fn profile_link(&self) -> String {
+        if self.profile.is_empty() {
+            profile_link("Unknown profile", self.user_id, None)
+        } else if self.profile_name.is_empty() {
+            profile_link(&self.profile, self.user_id, Some(&self.profile))
+        } else {
+            profile_link(&self.profile_name, self.user_id, Some(&self.profile))
+        }
+    }
+}
+
+/// Profiles seen for a user.
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct KnownProfiles {
+    /// Last known active profile
+    active: Option<String>,
+    active_updated: Option<Timestamp>,
+    /// Zoo name by profile ID
+    names: BTreeMap<String, String>,
+}
+
+impl KnownProfiles {
+    /// How long the last known active profile is trusted without asking the API, profile switches
+    /// the bot didn't see are misattributed for at most this long
+    const ACTIVE_TTL: i64 = 5 * 60;
+
+    fn find_by_name(&self, name: &str) -> Option<&str> {
+        let mut ids = self.names.iter().filter(|(_, n)| *n == name).map(|(id, _)| id.as_str());
+        match (ids.next(), ids.next()) {
+            (Some(id), None) => Some(id),
+            // Unknown or ambiguous
+            _ => None,
+        }
+    }
+
+    fn recent_active(&self, now: Timestamp) -> Option<&str> {
+        let updated = self.active_updated?;
+        if now.unix_timestamp() - updated.unix_timestamp() > Self::ACTIVE_TTL {
+            return None;
+        }
+        self.active.as_deref()
+    }
+
+    /// Remember the profile's name, and if it's the active one. Returns whether anything changed.
+    fn remember(&mut self, profile: &ZooProfileResponse, active: bool) -> bool {
+        let mut changed = self.names.get(&profile.profile_id) != Some(&profile.name);
+        if changed {
+            self.names.insert(profile.profile_id.clone(), profile.name.clone());
+        }
+        if active {
+            changed |= self.set_active(Some(profile.profile_id.clone()));
+        }
+        changed
+    }
+
+    /// Set the active profile. Returns whether it changed, only the time it was last seen is
+    /// updated otherwise.
+    fn set_active(&mut self, profile: Option<String>) -> bool {
+        self.active_updated = Some(Timestamp::now());
+        if self.active == profile {
+            return false;
+        }
+        self.active = profile;
+        true
+    }
 }
 
 #[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
@@ -208,7 +238,148 @@ struct Config {
     cooldowns: Vec<Cooldown>,
     disabled_users: BTreeSet<UserId>,
     manual_users: BTreeSet<UserId>,
+    /// Users that opted out of `/find` and other channel-wide lookups
+    hidden_users: BTreeSet<UserId>,
     channel_users: BTreeMap<ChannelId, BTreeSet<UserId>>,
+    known_profiles: BTreeMap<UserId, KnownProfiles>,
+    /// Zoo messages whose cooldowns were removed by their user, so edits don't add them back
+    dismissed_messages: BTreeSet<MessageId>,
+    /// Users that get an alert when a goal gets close to its next tier
+    goal_alerts: BTreeMap<UserId, GoalAlert>,
+    api: ApiConfig,
+    index: IndexConfig,
+}
+
+impl Config {
+    /// How long removed cooldowns stay removed when their Zoo message is edited
+    const DISMISSED_TTL: i64 = 24 * 60 * 60;
+
+    /// Add or update the cooldowns of a Zoo message. Returns the changed cooldowns, and whether
+    /// any of them are new to the message, edits of a tracked message are already reacted to.
+    fn track_cooldowns(
+        &mut self,
+        message_id: MessageId,
+        cooldowns: &[Cooldown],
+    ) -> (Vec<Cooldown>, bool) {
+        self.dismissed_messages.remove(&message_id);
+        let mut updated = Vec::with_capacity(cooldowns.len());
+        let mut react = false;
+        for cooldown in cooldowns {
+            if let Some(existing) = self.cooldowns.iter_mut().find(|existing| {
+                existing.kind == cooldown.kind
+                    && existing.user_id == cooldown.user_id
+                    && existing.profile == cooldown.profile
+            }) {
+                // Update existing cooldown
+                existing.channel_id = cooldown.channel_id;
+                existing.profile_name = cooldown.profile_name.clone();
+                // Check if timestamp is within 1 second of the existing one,
+                // if not, update it
+                let diff = (existing.timestamp.unix_timestamp()
+                    - cooldown.timestamp.unix_timestamp())
+                .abs();
+                if diff > 2 {
+                    existing.timestamp = cooldown.timestamp;
+                    react |= existing.message_id != Some(message_id);
+                    existing.message_id = Some(message_id);
+                    updated.push(existing.clone());
+                }
+            } else {
+                self.cooldowns.push(cooldown.clone());
+                updated.push(cooldown.clone());
+                react = true;
+            }
+        }
+        (updated, react)
+    }
+
+    /// Remove the cooldowns of a Zoo message, and keep edits of the message from adding them back.
+    fn dismiss_cooldowns(&mut self, message_id: MessageId, cooldowns: &[Cooldown], now: Timestamp) {
+        self.cooldowns.retain(|existing| {
+            !cooldowns.iter().any(|cooldown| {
+                existing.kind == cooldown.kind
+                    && existing.user_id == cooldown.user_id
+                    && existing.profile == cooldown.profile
+            })
+        });
+        self.dismissed_messages.insert(message_id);
+        self.dismissed_messages.retain(|id| {
+            now.unix_timestamp() - id.created_at().unix_timestamp() <= Self::DISMISSED_TTL
+        });
+    }
+
+    /// Users seen in the channel that can be shown in channel-wide lookups.
+    fn discoverable_users(&self, channel_id: ChannelId) -> Vec<UserId> {
+        self.channel_users
+            .get(&channel_id)
+            .into_iter()
+            .flatten()
+            .filter(|user_id| !self.hidden_users.contains(user_id))
+            .copied()
+            .collect()
+    }
+
+    /// Users seen in any channel that can be shown in channel-wide lookups.
+    fn all_discoverable_users(&self) -> BTreeSet<UserId> {
+        self.channel_users
+            .values()
+            .flatten()
+            .filter(|user_id| !self.hidden_users.contains(user_id))
+            .copied()
+            .collect()
+    }
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct ApiConfig {
+    /// Base URL of the Zoo API, e.g. a mirror or a local mock server
+    base_url: String,
+    /// How long fetched profiles are reused, in seconds
+    cache_ttl: u64,
+    /// Timeout of a single request, in seconds
+    timeout: u64,
+    /// Retries on rate limits, server errors and network errors
+    max_retries: u32,
+    /// Maximum requests per second, zero for unlimited
+    requests_per_second: f64,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        ApiConfig {
+            base_url: zoo_api::DEFAULT_BASE_URL.to_string(),
+            cache_ttl: 120,
+            timeout: 10,
+            max_retries: 3,
+            requests_per_second: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct IndexConfig {
+    /// How often channel users' profiles are refreshed, in seconds
+    refresh_interval: u64,
+    /// Users fetched at the same time
+    concurrency: usize,
+    /// Longest wait for a single profile, including retries, in seconds
+    fetch_timeout: u64,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        IndexConfig { refresh_interval: 900, concurrency: 4, fetch_timeout: 30 }
+    }
+}
+
+impl IndexConfig {
+    fn max_age(&self) -> TimeDelta { TimeDelta::seconds(self.refresh_interval as i64) }
+
+    fn build(&self) -> ProfileIndex {
+        ProfileIndex::new(self.concurrency, Duration::from_secs(self.fetch_timeout))
+    }
 }
 
 async fn load_config() -> Result<Config> {
@@ -230,6 +401,32 @@ async fn save_config(config: &Config) -> Result<()> {
         .with_context(|| format!("Failed to write to {}", config_path))
 }
 
+fn rules_path() -> PathBuf {
+    std::env::var("RULES_PATH").unwrap_or_else(|_| "rules.toml".to_string()).into()
+}
+
+/// Load the parser rules file if it changed since `last_modified`.
+async fn reload_rules(last_modified: &mut Option<SystemTime>) -> Result<()> {
+    let rules_path = rules_path();
+    let Ok(metadata) = tokio::fs::metadata(&rules_path).await else {
+        if last_modified.take().is_some() {
+            info!("Rules file {} removed, using built-in rules", rules_path.display());
+            parsers::set_rules(ParserRules::default());
+        }
+        return Ok(());
+    };
+    let modified = metadata.modified().ok();
+    if last_modified.is_some() && *last_modified == modified {
+        return Ok(());
+    }
+    // Don't retry a broken file until it changes again
+    *last_modified = modified;
+    let rules = ParserRules::load(&rules_path).await?;
+    info!("Loaded {} parser rules from {}", rules.len(), rules_path.display());
+    parsers::set_rules(rules);
+    Ok(())
+}
+
 async fn advertise_cooldowns(
     ctx: &SerenityContext,
     message: &Message,
@@ -246,7 +443,8 @@ async fn advertise_cooldowns(
         }) {
             let diff =
                 (existing.timestamp.unix_timestamp() - cooldown.timestamp.unix_timestamp()).abs();
-            if diff > 2 {
+            // Already added from this message before it was edited
+            if diff > 2 && existing.message_id != Some(message.id) {
                 updated.push(existing.clone());
             }
         } else {
@@ -271,39 +469,21 @@ async fn add_cooldowns(
     cooldowns: &[Cooldown],
     data: &Data,
 ) -> Result<()> {
-    let mut updated = Vec::with_capacity(cooldowns.len());
     let mut config = data.config.write().await;
-    for cooldown in cooldowns {
-        if let Some(existing) = config.cooldowns.iter_mut().find(|existing| {
-            existing.kind == cooldown.kind
-                && existing.user_id == cooldown.user_id
-                && existing.profile == cooldown.profile
-        }) {
-            // Update existing cooldown
-            existing.channel_id = cooldown.channel_id;
-            existing.profile_name = cooldown.profile_name.clone();
-            // Check if timestamp is within 1 second of the existing one,
-            // if not, update it
-            let diff =
-                (existing.timestamp.unix_timestamp() - cooldown.timestamp.unix_timestamp()).abs();
-            if diff > 2 {
-                existing.timestamp = cooldown.timestamp;
-                updated.push(existing.clone());
-            }
-        } else {
-            config.cooldowns.push(cooldown.clone());
-            updated.push(cooldown.clone());
-        }
-    }
+    let (updated, react) = config.track_cooldowns(message.id, cooldowns);
     save_config(&config).await?;
     drop(config);
     for cooldown in &updated {
         info!(
-            "Cooldown added: {} {} (user {}, profile {})",
-            cooldown.kind, cooldown.timestamp, cooldown.user_id, cooldown.profile
+            "Cooldown added: {} {} (user {}, profile {} from {:?})",
+            cooldown.kind,
+            cooldown.timestamp,
+            cooldown.user_id,
+            cooldown.profile,
+            cooldown.profile_source
         );
     }
-    if !updated.is_empty() {
+    if react {
         let reaction = ReactionType::Unicode("✅".to_string());
         message.react(ctx, reaction).await?;
     }
@@ -317,13 +497,7 @@ async fn remove_cooldowns(
     data: &Data,
 ) -> Result<()> {
     let mut config = data.config.write().await;
-    config.cooldowns.retain(|existing| {
-        !cooldowns.iter().any(|cooldown| {
-            existing.kind == cooldown.kind
-                && existing.user_id == cooldown.user_id
-                && existing.profile == cooldown.profile
-        })
-    });
+    config.dismiss_cooldowns(message.id, cooldowns, Timestamp::now());
     save_config(&config).await?;
     drop(config);
     for cooldown in cooldowns {
@@ -337,42 +511,119 @@ async fn remove_cooldowns(
     Ok(())
 }
 
-async fn extract_message_cooldowns(
+struct ResolvedProfile {
+    id: String,
+    name: String,
+    source: ProfileSource,
+}
+
+/// Find which profile a Zoo message is for. Prefers what the message itself shows, then the
+/// last known active profile, and only then asks the API for the current profile, which may
+/// have changed since the message was sent.
+async fn resolve_profile(
     message: &Message,
     user_id: UserId,
     data: &Data,
-) -> Result<Vec<Cooldown>> {
-    let mut cooldown_kinds = vec![];
-    if let Some(timestamp) = extract_rescue_cooldown(message) {
-        cooldown_kinds.push((CooldownKind::Rescue, timestamp));
-    }
-    if let Some(timestamp) = extract_card_cooldown(message) {
-        cooldown_kinds.push((CooldownKind::Card, timestamp));
-    }
-    if let Some(timestamp) = extract_quest_cooldown(message) {
-        cooldown_kinds.push((CooldownKind::Quest, timestamp));
+) -> Result<ResolvedProfile> {
+    let config = data.config.read().await;
+    let known = config.known_profiles.get(&user_id);
+    let profile = match profile_evidence(message, user_id) {
+        Some(ProfileEvidence::Id(id)) => Some(id),
+        Some(ProfileEvidence::Name(name) | ProfileEvidence::Switched(name)) => {
+            known.and_then(|known| known.find_by_name(&name)).map(str::to_string)
+        }
+        None => None,
+    };
+    if let Some(id) = profile {
+        if let Some(name) = known.and_then(|known| known.names.get(&id)) {
+            return Ok(ResolvedProfile { name: name.clone(), id, source: ProfileSource::Message });
+        }
+        drop(config);
+        let name = match data.zoo.fetch_profile(user_id.get(), Some(&id)).await {
+            Ok(profile) => {
+                remember_profile(data, user_id, &profile, false).await?;
+                profile.name.clone()
+            }
+            Err(e) => {
+                // Filled in by backfill_profiles
+                warn!("Failed to fetch profile {} for user ID {}: {:?}", id, user_id, e);
+                String::new()
+            }
+        };
+        return Ok(ResolvedProfile { id, name, source: ProfileSource::Message });
     }
-    if let Some(timestamp) = extract_mechanic_cooldown(message) {
-        cooldown_kinds.push((CooldownKind::Mechanic, timestamp));
+    if let Some(known) = known {
+        if let Some(id) = known.recent_active(Timestamp::now()) {
+            if let Some(name) = known.names.get(id) {
+                return Ok(ResolvedProfile {
+                    id: id.to_string(),
+                    name: name.clone(),
+                    source: ProfileSource::Cache,
+                });
+            }
+        }
     }
-    if let Some(timestamp) = extract_profile_cooldown(message) {
-        cooldown_kinds.push((CooldownKind::Profile, timestamp));
+    drop(config);
+    let profile = match data.zoo.fetch_profile(user_id.get(), None).await {
+        Ok(profile) => profile,
+        Err(e) => {
+            // Track it anyway, backfill_profiles fills in the profile later
+            warn!("Failed to fetch profile for user ID {}: {:?}", user_id, e);
+            return Ok(ResolvedProfile {
+                id: String::new(),
+                name: String::new(),
+                source: ProfileSource::Unresolved,
+            });
+        }
+    };
+    remember_profile(data, user_id, &profile, true).await?;
+    Ok(ResolvedProfile {
+        id: profile.profile_id.clone(),
+        name: profile.name.clone(),
+        source: ProfileSource::Api,
+    })
+}
+
+async fn remember_profile(
+    data: &Data,
+    user_id: UserId,
+    profile: &ZooProfileResponse,
+    active: bool,
+) -> Result<()> {
+    let mut config = data.config.write().await;
+    if config.known_profiles.entry(user_id).or_default().remember(profile, active) {
+        save_config(&config).await?;
     }
+    Ok(())
+}
+
+async fn extract_message_cooldowns(
+    message: &Message,
+    parsed: &ZooMessage,
+    user_id: UserId,
+    data: &Data,
+) -> Result<Vec<Cooldown>> {
+    let cooldown_kinds = parsed
+        .events()
+        .into_iter()
+        .filter_map(|event| Some((CooldownKind::from_event(event.kind)?, event.timestamp)))
+        .collect::<Vec<_>>();
     if cooldown_kinds.is_empty() {
         return Ok(vec![]);
     }
-    let profile = fetch_zoo_profile(&data.client, user_id.get(), None)
-        .await
-        .with_context(|| format!("Failed to fetch profile for user ID {}", user_id.get()))?;
+    let profile = resolve_profile(message, user_id, data).await?;
     let cooldowns = cooldown_kinds
         .into_iter()
         .map(|(kind, timestamp)| Cooldown {
             kind,
             channel_id: message.channel_id,
             user_id,
-            profile: profile.profile_id.clone(),
+            profile: profile.id.clone(),
             profile_name: profile.name.clone(),
+            profile_source: profile.source,
             timestamp,
+            seen: Some(message.timestamp),
+            message_id: Some(message.id),
         })
         .collect::<Vec<_>>();
     Ok(cooldowns)
@@ -386,10 +637,15 @@ async fn check_cooldown_message<'a>(
     if message.author.id != ZOO_USER_ID {
         return Ok(());
     }
-    let Some(interaction) = message.interaction.as_deref() else {
+    let parsed = ZooMessage::parse(message);
+    let Some((user_id, _)) = invoking_user(message, &parsed) else {
         return Ok(());
     };
-    let user_id = interaction.user.id;
+    if matches!(parsed, ZooMessage::RescueResult { .. } | ZooMessage::CooldownModifier { .. }) {
+        // Rescued animals
+        data.zoo.invalidate_user(user_id.get());
+        data.index.mark_stale(user_id);
+    }
     let mut config = data.config.write().await;
     // Add user to channel users if not already present
     if config.channel_users.entry(message.channel_id).or_insert_with(BTreeSet::new).insert(user_id)
@@ -400,8 +656,18 @@ async fn check_cooldown_message<'a>(
         return Ok(());
     }
     let manual = config.manual_users.contains(&user_id);
+    // Keep track of `/profiles` switches for attributing later messages
+    if let Some(ProfileEvidence::Switched(name)) = profile_evidence(message, user_id) {
+        data.zoo.invalidate_current(user_id.get());
+        let known = config.known_profiles.entry(user_id).or_default();
+        // Unknown name, forget the active profile so the API is asked next time
+        let profile = known.find_by_name(&name).map(str::to_string);
+        if known.set_active(profile) {
+            save_config(&config).await?;
+        }
+    }
     drop(config);
-    let cooldowns = extract_message_cooldowns(message, user_id, data).await?;
+    let cooldowns = extract_message_cooldowns(message, &parsed, user_id, data).await?;
     if cooldowns.is_empty() {
         return Ok(());
     }
@@ -412,6 +678,28 @@ async fn check_cooldown_message<'a>(
     }
 }
 
+async fn check_updated_message<'a>(
+    ctx: &'a SerenityContext,
+    new: Option<&Message>,
+    event: &MessageUpdateEvent,
+    data: &'a Data,
+) -> Result<()> {
+    // The user removed its cooldowns, don't add them back
+    if data.config.read().await.dismissed_messages.contains(&event.id) {
+        return Ok(());
+    }
+    if let Some(message) = new {
+        return check_cooldown_message(ctx, message, data).await;
+    }
+    // Not cached, only fetch edits that are known to be from Zoo
+    if event.author.as_ref().map(|author| author.id) != Some(ZOO_USER_ID) {
+        return Ok(());
+    }
+    let message =
+        event.channel_id.message(ctx, event.id).await.context("Fetching updated message")?;
+    check_cooldown_message(ctx, &message, data).await
+}
+
 async fn handle_reaction<'a>(
     ctx: &'a SerenityContext,
     add_reaction: &Reaction,
@@ -431,13 +719,11 @@ async fn handle_reaction<'a>(
     if message.author.id != ZOO_USER_ID {
         return Ok(());
     }
-    let Some(interaction) = message.interaction.as_deref() else {
-        return Ok(());
-    };
-    if user_id != interaction.user.id {
+    let parsed = ZooMessage::parse(&message);
+    if invoking_user(&message, &parsed).map(|(user_id, _)| user_id) != Some(user_id) {
         return Ok(());
     }
-    for cooldown in extract_message_cooldowns(&message, user_id, data).await? {
+    for cooldown in extract_message_cooldowns(&message, &parsed, user_id, data).await? {
         if cooldown.kind.emoji() == emoji {
             if add {
                 add_cooldowns(ctx, &message, &[cooldown], data).await?;
@@ -456,10 +742,13 @@ async fn handle_interaction<'a>(
     data: &'a Data,
 ) -> Result<()> {
     if let Interaction::Component(component) = interaction {
-        let Some(interaction) = component.message.interaction.as_deref() else {
+        // Our own messages are always command responses, there's nothing to parse
+        let Some((user_id, InvokerSource::InteractionMetadata | InvokerSource::Interaction)) =
+            invoking_user(&component.message, &ZooMessage::Unknown)
+        else {
             return Ok(());
         };
-        if interaction.user.id != component.user.id {
+        if user_id != component.user.id {
             component
                 .create_response(
                     ctx,
@@ -473,7 +762,7 @@ async fn handle_interaction<'a>(
             return Ok(());
         }
         match component.data.custom_id.as_str() {
-            "disable" | "enable" | "auto" | "manual" | "all" => {
+            "disable" | "enable" | "auto" | "manual" | "hide" | "show" | "all" => {
                 let mut config = data.config.write().await;
                 if component.data.custom_id == "enable" {
                     config.disabled_users.remove(&component.user.id);
@@ -483,6 +772,10 @@ async fn handle_interaction<'a>(
                     config.manual_users.remove(&component.user.id);
                 } else if component.data.custom_id == "manual" {
                     config.manual_users.insert(component.user.id);
+                } else if component.data.custom_id == "hide" {
+                    config.hidden_users.insert(component.user.id);
+                } else if component.data.custom_id == "show" {
+                    config.hidden_users.remove(&component.user.id);
                 }
                 save_config(&config).await?;
                 let (message, components) = create_cooldowns_message(
@@ -501,6 +794,31 @@ async fn handle_interaction<'a>(
                     .create_response(ctx, CreateInteractionResponse::UpdateMessage(message))
                     .await?;
             }
+            custom_id if custom_id.starts_with("profile:") => {
+                let ComponentInteractionDataKind::StringSelect { values } = &component.data.kind
+                else {
+                    return Ok(());
+                };
+                let user_id: UserId = custom_id.trim_start_matches("profile:").parse()?;
+                let user = user_id.to_user(ctx).await?;
+                let message = match create_profile_message(
+                    data,
+                    component.user.id,
+                    &user,
+                    values.first().map(String::as_str),
+                )
+                .await?
+                {
+                    Ok((embed, components)) => {
+                        CreateInteractionResponseMessage::new().embed(embed).components(components)
+                    }
+                    Err(message) => CreateInteractionResponseMessage::new().content(message),
+                };
+                let message = message.allowed_mentions(CreateAllowedMentions::new());
+                component
+                    .create_response(ctx, CreateInteractionResponse::UpdateMessage(message))
+                    .await?;
+            }
             _ => {
                 warn!("Unknown interaction component ID: {}", component.data.custom_id);
                 component.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
@@ -523,6 +841,11 @@ async fn event_handler<'a>(
                 error!("Error handling message: {:?}", e);
             }
         }
+        FullEvent::MessageUpdate { new, event, .. } => {
+            if let Err(e) = check_updated_message(ctx, new.as_ref(), event, data).await {
+                error!("Error handling message update: {:?}", e);
+            }
+        }
         FullEvent::ReactionAdd { add_reaction } => {
             if let Err(e) = handle_reaction(ctx, add_reaction, data, true).await {
                 error!("Error handling reaction: {:?}", e);
@@ -573,6 +896,18 @@ async fn botstatus(ctx: Context<'_>) -> Result<(), Error> {
     description.push_bold("Rust version: ").push(env!("VERGEN_RUSTC_SEMVER")).push_line(" 🦀");
     description.push_bold("Memory usage: ").push_line(memory);
     description.push_bold("Tracked cooldowns: ").push_line(config.cooldowns.len().to_string());
+    let stats = data.zoo.stats();
+    description.push_bold("Profile cache: ").push_line(format!(
+        "{} cached, {} hits, {} misses, {} coalesced",
+        stats.entries, stats.hits, stats.misses, stats.coalesced
+    ));
+    let (users, profiles) = data.index.len();
+    description
+        .push_bold("Profile index: ")
+        .push_line(format!("{} users, {} profiles", users, profiles));
+    description
+        .push_bold("Animal catalog: ")
+        .push_line(format!("{} animals", data.catalog.read().await.len()));
     let embed = CreateEmbed::default()
         .author(author)
         .description(description.build())
@@ -648,6 +983,13 @@ fn create_cooldowns_message(
         message.push_bold("enabled").push_line(" ✅");
     }
 
+    message.push("Shown in `/find`: ");
+    if config.hidden_users.contains(&user.as_ref().map_or(current_user, |user| user.id)) {
+        message.push_bold("no").push_line(" ❌");
+    } else {
+        message.push_bold("yes").push_line(" ✅");
+    }
+
     if cooldowns.is_empty() {
         if let Some(user) = &user {
             message.push("No cooldowns tracked for ").user(user).push_line(".");
@@ -695,6 +1037,14 @@ fn create_cooldowns_message(
                 CreateButton::new("manual").label("Manual mode").style(ButtonStyle::Secondary),
             );
         }
+        if config.hidden_users.contains(&current_user) {
+            buttons
+                .push(CreateButton::new("show").label("Show in /find").style(ButtonStyle::Primary));
+        } else {
+            buttons.push(
+                CreateButton::new("hide").label("Hide from /find").style(ButtonStyle::Secondary),
+            );
+        }
         if !show_all && config.owners.contains(&current_user) {
             buttons.push(CreateButton::new("all").label("Show all").style(ButtonStyle::Secondary));
         }
@@ -726,106 +1076,299 @@ async fn enable(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-/// Find an animal in any channel user's profile
+/// Hide your zoo from /find and other channel-wide lookups
+#[command(slash_command, ephemeral)]
+async fn hide(ctx: Context<'_>) -> Result<(), Error> {
+    let mut config = ctx.data().config.write().await;
+    config.hidden_users.insert(ctx.author().id);
+    save_config(&config).await?;
+    drop(config);
+    ctx.say("Your zoo is no longer shown in `/find` and other channel-wide lookups.\nUse `/show` to be shown again.")
+        .await?;
+    Ok(())
+}
+
+/// Show your zoo in /find and other channel-wide lookups
+#[command(slash_command, ephemeral)]
+async fn show(ctx: Context<'_>) -> Result<(), Error> {
+    let mut config = ctx.data().config.write().await;
+    config.hidden_users.remove(&ctx.author().id);
+    save_config(&config).await?;
+    drop(config);
+    ctx.say(
+        "Your zoo is shown in `/find` and other channel-wide lookups.\nUse `/hide` to opt out.",
+    )
+    .await?;
+    Ok(())
+}
+
+/// Find animals by name, family or rarity in channel users' profiles
 #[command(slash_command)]
-async fn find(ctx: Context<'_>, #[description = "Animal name"] name: String) -> Result<(), Error> {
+async fn find(
+    ctx: Context<'_>,
+    #[description = "Animal names, separated by commas"]
+    #[autocomplete = "autocomplete_animals"]
+    animals: Option<String>,
+    #[description = "Animal family"]
+    #[autocomplete = "autocomplete_family"]
+    family: Option<String>,
+    #[description = "Only common or rare animals"] rarity: Option<Rarity>,
+    #[description = "Fetch the latest profiles instead of using the index"] refresh: Option<bool>,
+) -> Result<(), Error> {
     // Start typing to show that the bot is searching
     ctx.defer().await?;
 
-    if !ANIMAL_NAMES.iter().any(|animal| animal.eq_ignore_ascii_case(&name)) {
-        let mut message = MessageBuilder::new();
-        message.push_bold_safe(&name).push(" is not a valid animal.");
+    let reply = |content: String| {
+        CreateReply::default().content(content).allowed_mentions(CreateAllowedMentions::new())
+    };
+    let catalog = ctx.data().catalog.read().await;
+    let query = parse_query(&catalog, animals.as_deref(), family.as_deref(), rarity);
+    drop(catalog);
+    let query = match query {
+        Ok(query) => query,
+        Err(message) => {
+            ctx.send(reply(message)).await?;
+            return Ok(());
+        }
+    };
 
-        let reply = CreateReply::default()
-            .content(message.build())
-            .allowed_mentions(CreateAllowedMentions::new());
-        ctx.send(reply).await?;
+    let user_ids = ctx.data().config.read().await.discoverable_users(ctx.channel_id());
+    let data = ctx.data();
+    // Users the crawler hasn't seen yet, e.g. the first search in a channel
+    let to_fetch =
+        if refresh.unwrap_or(false) { user_ids.clone() } else { data.index.missing(&user_ids) };
+    if to_fetch.is_empty() {
+        ctx.send(reply(find_animal(&data.index.lookup(&user_ids), &query, None)?)).await?;
         return Ok(());
     }
+    let total = to_fetch.len();
+    let mut tasks = data.index.spawn_refresh(&data.zoo, to_fetch);
+    let content = find_animal(&data.index.lookup(&user_ids), &query, Some((0, total)))?;
+    let handle = ctx.send(reply(content)).await?;
+    let mut last_edit = Instant::now();
+    let mut done = 0;
+    while let Some(result) = tasks.join_next().await {
+        if let Err(e) = result {
+            error!("Failed to refresh profiles: {:?}", e);
+        }
+        done += 1;
+        // Show results as they come in, without hitting Discord's rate limits
+        if done < total && last_edit.elapsed() >= FIND_EDIT_INTERVAL {
+            let content = find_animal(&data.index.lookup(&user_ids), &query, Some((done, total)))?;
+            handle.edit(ctx, reply(content)).await?;
+            last_edit = Instant::now();
+        }
+    }
+    handle.edit(ctx, reply(find_animal(&data.index.lookup(&user_ids), &query, None)?)).await?;
+    update_catalog(&data.index, &data.catalog).await;
+    Ok(())
+}
 
-    let config = ctx.data().config.read().await;
-    let user_ids = config
-        .channel_users
-        .get(&ctx.channel_id())
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+enum Rarity {
+    Common,
+    Rare,
+}
+
+/// Resolve the `/find` options with the catalog. Returns the reply if an option is invalid.
+fn parse_query(
+    catalog: &Catalog,
+    animals: Option<&str>,
+    family: Option<&str>,
+    rarity: Option<Rarity>,
+) -> Result<AnimalQuery, String> {
+    let mut query =
+        AnimalQuery { rare: rarity.map(|rarity| rarity == Rarity::Rare), ..Default::default() };
+    for name in
+        animals.unwrap_or_default().split(',').map(str::trim).filter(|name| !name.is_empty())
+    {
+        let Some(animal) = catalog.get(name) else {
+            let suggestions = catalog.suggest(name, 3);
+            let suggestions =
+                suggestions.iter().map(|animal| animal.name.as_str()).collect::<Vec<_>>();
+            return Err(not_found_message(name, "a valid animal", &suggestions));
+        };
+        if !query.names.contains(&animal.name) {
+            query.names.push(animal.name.clone());
+        }
+    }
+    if let Some(family) = family.map(str::trim).filter(|family| !family.is_empty()) {
+        let Some(found) = catalog.get_family(family) else {
+            return Err(not_found_message(
+                family,
+                "a known family",
+                &catalog.suggest_family(family, 3),
+            ));
+        };
+        query.family = Some(found.to_string());
+    }
+    if query.is_empty() {
+        return Err("Choose some animals, a family or a rarity to search for.".to_string());
+    }
+    Ok(query)
+}
+
+/// E.g. "**x** is not a valid animal. Did you mean **A**, **B** or **C**?"
+fn not_found_message(name: &str, what: &str, suggestions: &[&str]) -> String {
+    let mut message = MessageBuilder::new();
+    message.push_bold_safe(name).push(format!(" is not {}.", what));
+    for (i, suggestion) in suggestions.iter().enumerate() {
+        message.push(match i {
+            0 => " Did you mean ",
+            _ if i + 1 == suggestions.len() => " or ",
+            _ => ", ",
+        });
+        message.push_bold_safe(*suggestion);
+    }
+    if !suggestions.is_empty() {
+        message.push("?");
+    }
+    message.build()
+}
+
+/// Autocomplete the last of the comma separated animals.
+async fn autocomplete_animals(
+    ctx: Context<'_>,
+    partial: &str,
+) -> impl Iterator<Item = AutocompleteChoice> {
+    let (chosen, last) = match partial.rfind(',') {
+        Some(i) => (&partial[..=i], &partial[i + 1..]),
+        None => ("", partial),
+    };
+    let chosen_names = chosen.split(',').map(str::trim).collect::<Vec<_>>();
+    let prefix = if chosen.is_empty() { String::new() } else { format!("{} ", chosen.trim_end()) };
+    let catalog = ctx.data().catalog.read().await;
+    let choices = catalog
+        .search(last.trim())
         .into_iter()
-        .flatten()
-        .cloned()
+        .filter(|animal| !chosen_names.iter().any(|name| name.eq_ignore_ascii_case(&animal.name)))
+        .map(|animal| {
+            let label =
+                if animal.rare { format!("{} (rare)", animal.label()) } else { animal.label() };
+            AutocompleteChoice::new(
+                format!("{}{}", prefix, label),
+                format!("{}{}", prefix, animal.name),
+            )
+        })
         .collect::<Vec<_>>();
-    let mut profiles = vec![];
-    for user_id in user_ids {
-        let profile = fetch_zoo_profile(&ctx.data().client, user_id.get(), None)
-            .await
-            .with_context(|| format!("Failed to fetch profile for user ID {}", user_id))?;
-        // Also fetch other profiles from the same user
-        for profile_name in &profile.profiles {
-            if profile_name == &profile.profile_id {
-                continue;
-            }
-            let profile =
-                fetch_zoo_profile(&ctx.data().client, user_id.get(), Some(profile_name.as_str()))
-                    .await
-                    .with_context(|| {
-                        format!(
-                            "Failed to fetch profile for user ID {} (profile {})",
-                            user_id, profile_name
-                        )
-                    })?;
-            profiles.push(profile);
-        }
-        profiles.push(profile);
-    }
-    drop(config);
+    choices.into_iter()
+}
+
+async fn autocomplete_family(
+    ctx: Context<'_>,
+    partial: &str,
+) -> impl Iterator<Item = AutocompleteChoice> {
+    let catalog = ctx.data().catalog.read().await;
+    let choices = catalog
+        .search_families(partial)
+        .into_iter()
+        .map(|family| AutocompleteChoice::new(family, family))
+        .collect::<Vec<_>>();
+    choices.into_iter()
+}
+
+/// Shortest time between edits of a `/find` reply.
+const FIND_EDIT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most animals listed for a single profile by `/find`.
+const FIND_MAX_ANIMALS: usize = 5;
+
+/// Search the profiles for animals, and format the results grouped by profile. `progress` is the
+/// number of users checked so far and in total, if the search is still going.
+fn find_animal(
+    lookup: &Lookup,
+    query: &AnimalQuery,
+    progress: Option<(usize, usize)>,
+) -> Result<String> {
+    let profiles = &lookup.profiles;
     struct FoundAnimal<'a> {
-        profile: &'a ZooProfileResponse,
         animal: &'a ZooProfileAnimal,
         // Profile also has the rare version of the animal
         has_rare: bool,
     }
+    struct FoundProfile<'a> {
+        profile: &'a ZooProfileResponse,
+        animals: Vec<FoundAnimal<'a>>,
+    }
+    impl FoundProfile<'_> {
+        /// Only pinned animals, that the owner wants to keep
+        fn all_pinned(&self) -> bool { self.animals.iter().all(|found| found.animal.pinned) }
+
+        fn has_rare(&self) -> bool { self.animals.iter().any(|found| found.has_rare) }
+
+        fn amount(&self) -> u32 { self.animals.iter().map(|found| found.animal.amount).sum() }
+    }
     let mut found = vec![];
-    for profile in &profiles {
-        if let Some(animal) = profile
+    for profile in profiles {
+        let mut animals = profile
             .animals
             .iter()
-            .find(|animal| animal.amount > 0 && animal.name.eq_ignore_ascii_case(&name))
-        {
-            let has_rare = !animal.rare
-                && profile
-                    .animals
-                    .iter()
-                    .any(|v| v.rare && v.amount > 0 && v.family == animal.family);
-            found.push(FoundAnimal { profile, animal, has_rare });
+            .filter(|animal| query.matches(animal))
+            .map(|animal| {
+                let has_rare = !animal.rare
+                    && profile
+                        .animals
+                        .iter()
+                        .any(|v| v.rare && v.amount > 0 && v.family == animal.family);
+                FoundAnimal { animal, has_rare }
+            })
+            .collect::<Vec<_>>();
+        if animals.is_empty() {
+            continue;
         }
+        // Pinned animals last, then animals with the rare first, then by amount
+        animals.sort_by(|a, b| {
+            a.animal.pinned.cmp(&b.animal.pinned).then_with(|| {
+                b.has_rare.cmp(&a.has_rare).then_with(|| b.animal.amount.cmp(&a.animal.amount))
+            })
+        });
+        found.push(FoundProfile { profile, animals });
     }
     found.sort_by(|a, b| {
-        // Pinned animals last, then profiles with rare first, then by amount
-        a.animal.pinned.cmp(&b.animal.pinned).then_with(|| {
-            b.has_rare.cmp(&a.has_rare).then_with(|| b.animal.amount.cmp(&a.animal.amount))
+        // Profiles with only pinned animals last, then profiles with rare first, then by the
+        // number of animals found
+        a.all_pinned().cmp(&b.all_pinned()).then_with(|| {
+            b.has_rare()
+                .cmp(&a.has_rare())
+                .then_with(|| b.animals.len().cmp(&a.animals.len()))
+                .then_with(|| b.amount().cmp(&a.amount()))
         })
     });
     let mut message = MessageBuilder::new();
     if found.is_empty() {
         message
             .push("Couldn't find ")
-            .push_bold_safe(&name)
+            .push(query.describe())
             .push(format!(" in {} profiles.", profiles.len()));
     } else {
-        // let mut message = format!("Found **{}** in {} profiles:\n", name, found.len());
         message
             .push("Found ")
-            .push_bold_safe(&name)
+            .push(query.describe())
             .push_line(format!(" in {} profiles:", found.len()));
         for found in found.iter().take(10) {
             let user_id: UserId = found.profile.user_id.parse()?;
             message
                 .push("- ")
-                .push_bold(format!("{}x", found.animal.amount))
-                .push(" in ")
-                .push(profile_link(&found.profile.name, user_id, Some(&found.profile.profile_id)));
-            if found.has_rare {
-                message.push(" 🌟");
+                .push(profile_link(&found.profile.name, user_id, Some(&found.profile.profile_id)))
+                .push(": ");
+            for (i, animal) in found.animals.iter().take(FIND_MAX_ANIMALS).enumerate() {
+                if i > 0 {
+                    message.push(", ");
+                }
+                message.push_bold(format!("{}x", animal.animal.amount)).push(" ");
+                if !animal.animal.emoji.is_empty() {
+                    message.push(format!("{} ", animal.animal.emoji));
+                }
+                message.push_safe(&animal.animal.name);
+                if animal.has_rare {
+                    message.push(" 🌟");
+                }
+                if animal.animal.pinned {
+                    message.push(" 📌");
+                }
             }
-            if found.animal.pinned {
-                message.push(" 📌");
+            if found.animals.len() > FIND_MAX_ANIMALS {
+                message.push(format!(" and {} more", found.animals.len() - FIND_MAX_ANIMALS));
             }
             message.push_line("");
         }
@@ -833,28 +1376,308 @@ async fn find(ctx: Context<'_>, #[description = "Animal name"] name: String) ->
             message.push_line(format!("... and {} more", found.len() - 10));
         }
     }
-    let reply = CreateReply::default()
-        .content(message.build())
-        .allowed_mentions(CreateAllowedMentions::new());
-    ctx.send(reply).await?;
-    Ok(())
+    push_lookup_status(&mut message, lookup, progress);
+    Ok(message.build())
 }
 
-fn format_cooldown(cooldown: &Cooldown) -> String {
-    let cooldown_msg = format!(
-        "{} {} {}",
-        cooldown.kind.emoji(),
-        cooldown.kind,
-        FormattedTimestamp::new(cooldown.timestamp, Some(FormattedTimestampStyle::RelativeTime)),
-    );
-    if cooldown.kind == CooldownKind::Profile {
-        cooldown_msg
+/// Add which profiles couldn't be checked, and how old the others are.
+fn push_lookup_status(
+    message: &mut MessageBuilder,
+    lookup: &Lookup,
+    progress: Option<(usize, usize)>,
+) {
+    // Users without a zoo have no profiles to check
+    let unchecked = lookup.skipped.iter().filter(|(reason, _)| **reason != SkipReason::NoZoo);
+    let count = unchecked.clone().map(|(_, count)| count).sum::<usize>();
+    if count > 0 {
+        let reasons = unchecked
+            .map(|(reason, count)| format!("{} {}", count, reason))
+            .collect::<Vec<_>>()
+            .join(", ");
+        message
+            .push_line("")
+            .push_italic(format!("{} profiles could not be checked ({})", count, reasons));
+    }
+    if let Some(count) = lookup.skipped.get(&SkipReason::NoZoo) {
+        message.push_line("").push_italic(format!("{} users without a zoo", count));
+    }
+    if let Some((done, total)) = progress {
+        message.push_line("").push_italic(format!("Checking profiles… {}/{} users", done, total));
+    } else if let Some(oldest) = lookup.oldest {
+        message.push_line("").push_italic(format!(
+            "Profiles as of {}",
+            FormattedTimestamp::new(oldest, Some(FormattedTimestampStyle::RelativeTime))
+        ));
+    }
+}
+
+/// List animals missing from your zoo, and how many channel users have them
+#[command(slash_command)]
+async fn missing(
+    ctx: Context<'_>,
+    #[description = "Only rare animals"] rare: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = ctx.data();
+    let reply = |content: String| {
+        CreateReply::default().content(content).allowed_mentions(CreateAllowedMentions::new())
+    };
+    let profile = match data.zoo.fetch_profile(ctx.author().id.get(), None).await {
+        Ok(profile) => profile,
+        Err(e) => {
+            ctx.send(reply(format!("Couldn't fetch your profile: {}", e))).await?;
+            return Ok(());
+        }
+    };
+    data.catalog.write().await.update([profile.as_ref()]);
+    let mut user_ids = data.config.read().await.discoverable_users(ctx.channel_id());
+    user_ids.retain(|user_id| *user_id != ctx.author().id);
+    index_missing(data, &user_ids).await;
+    let catalog = data.catalog.read().await;
+    let content =
+        missing_animals(&catalog, &profile, &data.index.lookup(&user_ids), rare.unwrap_or(false))?;
+    drop(catalog);
+    ctx.send(reply(content)).await?;
+    Ok(())
+}
+
+/// Compare two zoos side by side
+#[command(slash_command)]
+async fn compare(
+    ctx: Context<'_>,
+    #[description = "User to compare with"] user: User,
+    #[description = "Second user, yourself by default"] other: Option<User>,
+    #[description = "Profile name or ID of the first user, the current one by default"]
+    profile: Option<String>,
+    #[description = "Profile name or ID of the second user, the current one by default"]
+    other_profile: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = ctx.data();
+    let reply = |content: String| {
+        CreateReply::default().content(content).allowed_mentions(CreateAllowedMentions::new())
+    };
+    let other = other.unwrap_or_else(|| ctx.author().clone());
+    let mut profiles = vec![];
+    for (user, profile) in [(&user, profile), (&other, other_profile)] {
+        let id = profile_option(&*data.config.read().await, user.id, profile.as_deref());
+        let content = match data.zoo.fetch_profile(user.id.get(), id.as_deref()).await {
+            // Private profiles are only shown to their owner
+            Ok(profile) if profile.private && user.id != ctx.author().id => MessageBuilder::new()
+                .push("The profile of ")
+                .user(user)
+                .push(" is private.")
+                .build(),
+            Ok(profile) => {
+                profiles.push(profile);
+                continue;
+            }
+            Err(e) => MessageBuilder::new()
+                .push("Couldn't fetch the profile of ")
+                .user(user)
+                .push(format!(": {}", e))
+                .build(),
+        };
+        ctx.send(reply(content)).await?;
+        return Ok(());
+    }
+    ctx.send(reply(compare_profiles(&profiles[0], &profiles[1])?)).await?;
+    Ok(())
+}
+
+/// Profile ID for a profile name or ID given in a command, `None` for the current profile.
+fn profile_option(config: &Config, user_id: UserId, profile: Option<&str>) -> Option<String> {
+    let profile = profile.map(str::trim).filter(|profile| !profile.is_empty())?;
+    let known = config.known_profiles.get(&user_id);
+    let id = known.and_then(|known| known.find_by_name(profile)).unwrap_or(profile);
+    Some(id.to_string())
+}
+
+/// Show a summary of a Zoo profile
+#[command(slash_command)]
+async fn profile(
+    ctx: Context<'_>,
+    #[description = "Selected user, yourself by default"] user: Option<User>,
+    #[description = "Profile name or ID, the current one by default"] profile: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let user = user.unwrap_or_else(|| ctx.author().clone());
+    let id = profile_option(&*ctx.data().config.read().await, user.id, profile.as_deref());
+    let reply =
+        match create_profile_message(ctx.data(), ctx.author().id, &user, id.as_deref()).await? {
+            Ok((embed, components)) => CreateReply::default().embed(embed).components(components),
+            Err(message) => CreateReply::default().content(message),
+        };
+    ctx.send(reply.allowed_mentions(CreateAllowedMentions::new())).await?;
+    Ok(())
+}
+
+/// List goals of all your profiles, the closest to their next tier first
+#[command(slash_command)]
+async fn goals(
+    ctx: Context<'_>,
+    #[description = "Selected user, yourself by default"] user: Option<User>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let user = user.unwrap_or_else(|| ctx.author().clone());
+    let content = match fetch_all_profiles(&ctx.data().zoo, user.id).await {
+        Ok(mut profiles) => {
+            // Private profiles are only shown to their owner
+            if user.id != ctx.author().id {
+                profiles.retain(|profile| !profile.private);
+            }
+            format_goals(user.id, &profiles)
+        }
+        Err(e) => MessageBuilder::new()
+            .push("Couldn't fetch the profiles of ")
+            .user(&user)
+            .push(format!(": {}", e))
+            .build(),
+    };
+    let reply =
+        CreateReply::default().content(content).allowed_mentions(CreateAllowedMentions::new());
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Get an alert in this channel when one of your goals gets close to its next tier
+#[command(slash_command, ephemeral)]
+async fn goalalert(
+    ctx: Context<'_>,
+    #[description = "Percent of the next tier to alert at, 90 by default, 0 to turn off"]
+    #[max = 100]
+    threshold: Option<u32>,
+) -> Result<(), Error> {
+    let threshold = threshold.unwrap_or(90);
+    let mut config = ctx.data().config.write().await;
+    if threshold == 0 {
+        config.goal_alerts.remove(&ctx.author().id);
+    } else {
+        let alert = config.goal_alerts.entry(ctx.author().id).or_insert_with(|| GoalAlert {
+            channel_id: ctx.channel_id(),
+            threshold,
+            snapshot: BTreeMap::new(),
+        });
+        alert.channel_id = ctx.channel_id();
+        alert.threshold = threshold;
+    }
+    save_config(&config).await?;
+    drop(config);
+    if threshold == 0 {
+        ctx.say("No longer sending goal alerts.").await?;
+    } else {
+        ctx.say(format!(
+            "Sending an alert in this channel when a goal reaches {}% of its next tier.\nUse `/goalalert threshold:0` to stop.",
+            threshold
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+/// Fetch the current profile of a user and all of their other profiles. Other profiles that
+/// can't be fetched are left out.
+async fn fetch_all_profiles(
+    zoo: &ZooClient,
+    user_id: UserId,
+) -> Result<Vec<Arc<ZooProfileResponse>>, ZooError> {
+    let current = zoo.fetch_profile(user_id.get(), None).await?;
+    let mut profiles = vec![];
+    for profile in current.profiles.iter().filter(|profile| **profile != current.profile_id) {
+        match zoo.fetch_profile(user_id.get(), Some(profile)).await {
+            Ok(profile) => profiles.push(profile),
+            Err(e) => warn!("Failed to fetch profile {} of user ID {}: {:?}", profile, user_id, e),
+        }
+    }
+    profiles.insert(0, current);
+    Ok(profiles)
+}
+
+/// Show your active quest, when it finishes and who in the channel has the animal
+#[command(slash_command)]
+async fn quest(
+    ctx: Context<'_>,
+    #[description = "Profile name or ID, the current one by default"] profile: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = ctx.data();
+    let reply = |content: String| {
+        CreateReply::default().content(content).allowed_mentions(CreateAllowedMentions::new())
+    };
+    let user_id = ctx.author().id;
+    let id = profile_option(&*data.config.read().await, user_id, profile.as_deref());
+    let profile = match data.zoo.fetch_profile(user_id.get(), id.as_deref()).await {
+        Ok(profile) => profile,
+        Err(e) => {
+            ctx.send(reply(format!("Couldn't fetch your profile: {}", e))).await?;
+            return Ok(());
+        }
+    };
+    let config = data.config.read().await;
+    let cooldown = config
+        .cooldowns
+        .iter()
+        .find(|cooldown| {
+            cooldown.kind == CooldownKind::Quest
+                && cooldown.user_id == user_id
+                && cooldown.profile == profile.profile_id
+        })
+        .cloned();
+    let mut user_ids = config.discoverable_users(ctx.channel_id());
+    drop(config);
+    user_ids.retain(|other| *other != user_id);
+    index_missing(data, &user_ids).await;
+    let content = format_quest_status(&profile, cooldown.as_ref(), &data.index.lookup(&user_ids))?;
+    ctx.send(reply(content)).await?;
+    Ok(())
+}
+
+/// Fetch users the crawler hasn't seen yet, e.g. the first lookup in a channel.
+async fn index_missing(data: &Data, user_ids: &[UserId]) {
+    let to_fetch = data.index.missing(user_ids);
+    if !to_fetch.is_empty() {
+        data.index.refresh(&data.zoo, to_fetch).await;
+        update_catalog(&data.index, &data.catalog).await;
+    }
+}
+
+/// Suggest trades between channel users, based on duplicates and missing animals
+#[command(slash_command)]
+async fn trades(
+    ctx: Context<'_>,
+    #[description = "Only trades with this user"] user: Option<User>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = ctx.data();
+    let user_ids = data.config.read().await.discoverable_users(ctx.channel_id());
+    index_missing(data, &user_ids).await;
+    let lookup = data.index.lookup(&user_ids);
+    let catalog = data.catalog.read().await;
+    let content = format_trades(&catalog, &lookup, user.map(|user| user.id));
+    drop(catalog);
+    let reply =
+        CreateReply::default().content(content).allowed_mentions(CreateAllowedMentions::new());
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+fn format_cooldown(cooldown: &Cooldown) -> String {
+    let cooldown_msg = format!(
+        "{} {} {}",
+        cooldown.kind.emoji(),
+        cooldown.kind,
+        FormattedTimestamp::new(cooldown.timestamp, Some(FormattedTimestampStyle::RelativeTime)),
+    );
+    if cooldown.kind == CooldownKind::Profile {
+        cooldown_msg
     } else {
-        MessageBuilder::new()
-            .push(profile_link(&cooldown.profile_name, cooldown.user_id, Some(&cooldown.profile)))
-            .push(" ")
-            .push(cooldown_msg)
-            .build()
+        MessageBuilder::new().push(cooldown.profile_link()).push(" ").push(cooldown_msg).build()
     }
 }
 
@@ -878,10 +1701,77 @@ impl CacheHttp for MyCacheHttp {
 async fn run_notifications(
     config: &RwLock<Config>,
     http: &MyCacheHttp,
-    client: &reqwest::Client,
+    zoo: &ZooClient,
 ) -> Result<(), Error> {
-    let mut config = config.write().await;
     let now = Timestamp::now();
+    // Not holding the lock while waiting for the API
+    let user_ids = profile_notified_users(&*config.read().await, now);
+    let current_profiles = fetch_current_profiles(zoo, user_ids).await;
+    let mut config = config.write().await;
+    let (notifications, changed) = collect_notifications(&mut config, &current_profiles, now);
+    if changed {
+        save_config(&config).await?;
+    }
+    drop(config);
+    send_notifications(http, notifications).await;
+    Ok(())
+}
+
+async fn send_notifications(http: &MyCacheHttp, notifications: Vec<Notification>) {
+    for notification in notifications {
+        let message = CreateMessage::default()
+            .content(notification.content)
+            .allowed_mentions(CreateAllowedMentions::new().users([notification.user_id]));
+        if let Err(e) = notification.channel_id.send_message(http, message).await {
+            error!("Failed to send message: {:?}", e);
+        }
+    }
+}
+
+/// How often goals are checked for alerts.
+const GOAL_ALERT_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Fetch the profiles of users with goal alerts, and alert about goals that reached the threshold.
+async fn run_goal_alerts(
+    config: &RwLock<Config>,
+    http: &MyCacheHttp,
+    zoo: &ZooClient,
+) -> Result<(), Error> {
+    let user_ids = config.read().await.goal_alerts.keys().copied().collect::<Vec<_>>();
+    let mut profiles = BTreeMap::new();
+    for user_id in user_ids {
+        match fetch_all_profiles(zoo, user_id).await {
+            Ok(fetched) => {
+                profiles.insert(user_id, fetched);
+            }
+            Err(e) => {
+                warn!("Failed to fetch profiles of user ID {} for goal alerts: {:?}", user_id, e)
+            }
+        }
+    }
+    let mut config = config.write().await;
+    let (notifications, changed) = collect_goal_alerts(&mut config, &profiles);
+    if changed {
+        save_config(&config).await?;
+    }
+    drop(config);
+    send_notifications(http, notifications).await;
+    Ok(())
+}
+
+struct Notification {
+    channel_id: ChannelId,
+    user_id: UserId,
+    content: String,
+}
+
+/// Remove cooldowns that finished before `now`, and build their notifications. Also returns
+/// whether the config changed.
+fn collect_notifications(
+    config: &mut Config,
+    current_profiles: &BTreeMap<UserId, Arc<ZooProfileResponse>>,
+    now: Timestamp,
+) -> (Vec<Notification>, bool) {
     let mut any_expired = false;
     let mut messages = vec![];
     for cooldown in &config.cooldowns {
@@ -891,10 +1781,7 @@ async fn run_notifications(
                 cooldown.kind, cooldown.timestamp, cooldown.user_id, cooldown.profile
             );
             any_expired = true;
-            if config.disabled_users.contains(&cooldown.user_id)
-                // Don't notify if it expired more than 10 minutes ago
-                || *cooldown.timestamp < now.sub(TimeDelta::try_minutes(10).unwrap())
-            {
+            if !is_notified(config, cooldown, now) {
                 // Remove but don't notify
                 continue;
             }
@@ -904,50 +1791,213 @@ async fn run_notifications(
                 .push(format!(" {} {}", cooldown.kind.emoji(), cooldown.kind))
                 .push(" cooldown finished");
             if cooldown.kind != CooldownKind::Profile {
-                message.push(" for ").push(profile_link(
-                    &cooldown.profile_name,
-                    cooldown.user_id,
-                    Some(&cooldown.profile),
-                ));
-                let current_profile = fetch_zoo_profile(client, cooldown.user_id.get(), None)
-                    .await
-                    .with_context(|| {
-                        format!("Failed to fetch profile for user ID {}", cooldown.user_id.get())
-                    })?;
-                if current_profile.profile_id == cooldown.profile {
-                    message.push(" (current profile)");
-                } else {
-                    message
-                        .push("\n\nCurrent profile: ")
-                        .push(profile_link(
-                            &current_profile.name,
-                            cooldown.user_id,
-                            Some(&current_profile.profile_id),
-                        ))
-                        .push(". Switch profiles with: ")
-                        .push_codeblock_safe(
-                            format!("/profiles profile:{}", cooldown.profile),
-                            None,
-                        );
+                message.push(" for ").push(cooldown.profile_link());
+                match current_profiles.get(&cooldown.user_id) {
+                    Some(current_profile) if current_profile.profile_id == cooldown.profile => {
+                        message.push(" (current profile)");
+                    }
+                    Some(current_profile) if !cooldown.profile.is_empty() => {
+                        message
+                            .push("\n\nCurrent profile: ")
+                            .push(profile_link(
+                                &current_profile.name,
+                                cooldown.user_id,
+                                Some(&current_profile.profile_id),
+                            ))
+                            .push(". Switch profiles with: ")
+                            .push_codeblock_safe(
+                                format!("/profiles profile:{}", cooldown.profile),
+                                None,
+                            );
+                    }
+                    _ => {}
                 }
             }
-            let reply = CreateMessage::default()
-                .content(message.build())
-                .allowed_mentions(CreateAllowedMentions::new().users([cooldown.user_id]));
-            messages.push((cooldown.channel_id, reply));
+            messages.push(Notification {
+                channel_id: cooldown.channel_id,
+                user_id: cooldown.user_id,
+                content: message.build(),
+            });
         }
     }
+    for (user_id, profile) in current_profiles {
+        config.known_profiles.entry(*user_id).or_default().remember(profile, true);
+    }
     if any_expired {
         config.cooldowns.retain(|cooldown| now < cooldown.timestamp);
-        save_config(&config).await?;
     }
+    (messages, any_expired)
+}
+
+/// Whether a cooldown that finished before `now` gets a notification.
+fn is_notified(config: &Config, cooldown: &Cooldown, now: Timestamp) -> bool {
+    !config.disabled_users.contains(&cooldown.user_id)
+        // Don't notify if it expired more than 10 minutes ago
+        && *cooldown.timestamp >= now.sub(TimeDelta::try_minutes(10).unwrap())
+}
+
+/// Users that get a notification mentioning their current profile.
+fn profile_notified_users(config: &Config, now: Timestamp) -> BTreeSet<UserId> {
+    config
+        .cooldowns
+        .iter()
+        .filter(|cooldown| {
+            now >= cooldown.timestamp
+                && cooldown.kind != CooldownKind::Profile
+                && is_notified(config, cooldown, now)
+        })
+        .map(|cooldown| cooldown.user_id)
+        .collect()
+}
+
+/// Fetch the current profiles of users, leaving out the ones that failed.
+async fn fetch_current_profiles(
+    zoo: &ZooClient,
+    user_ids: BTreeSet<UserId>,
+) -> BTreeMap<UserId, Arc<ZooProfileResponse>> {
+    let mut profiles = BTreeMap::new();
+    for user_id in user_ids {
+        match zoo.fetch_profile(user_id.get(), None).await {
+            Ok(profile) => {
+                profiles.insert(user_id, profile);
+            }
+            // Nothing to compare against
+            Err(ZooError::NoZoo | ZooError::Private) => {}
+            Err(e) => {
+                // Notify without the current profile
+                warn!("Failed to fetch profile for user ID {}: {:?}", user_id.get(), e);
+            }
+        }
+    }
+    profiles
+}
+
+/// Refresh the index with the profiles of every channel user, and the catalog from the index.
+async fn crawl_profiles(
+    config: &RwLock<Config>,
+    index: &Arc<ProfileIndex>,
+    catalog: &RwLock<Catalog>,
+    zoo: &Arc<ZooClient>,
+) {
+    let config = config.read().await;
+    let user_ids = config.all_discoverable_users();
+    let max_age = config.index.max_age();
     drop(config);
-    for (channel_id, message) in messages {
-        if let Err(e) = channel_id.send_message(http, message).await {
-            error!("Failed to send message: {:?}", e);
+    // Also forgets users that opted out
+    index.retain(&user_ids);
+    let user_ids = index.needs_refresh(&Vec::from_iter(user_ids), max_age);
+    if user_ids.is_empty() {
+        return;
+    }
+    info!("Refreshing the profiles of {} users", user_ids.len());
+    index.refresh(zoo, user_ids).await;
+    update_catalog(index, catalog).await;
+}
+
+async fn update_catalog(index: &ProfileIndex, catalog: &RwLock<Catalog>) {
+    let profiles = index.all_profiles();
+    let changed = catalog.write().await.update(profiles.iter().map(AsRef::as_ref));
+    if changed > 0 {
+        info!("Updated {} animals in the catalog", changed);
+    }
+}
+
+/// Fill in the profile of cooldowns that were tracked while the API was unavailable.
+async fn backfill_profiles(config: &RwLock<Config>, zoo: &ZooClient) -> Result<(), Error> {
+    let unresolved = config
+        .read()
+        .await
+        .cooldowns
+        .iter()
+        .filter(|cooldown| cooldown.is_unresolved())
+        .map(|cooldown| (cooldown.user_id, cooldown.profile.clone()))
+        .collect::<BTreeSet<_>>();
+    let mut profiles = vec![];
+    let mut failed = vec![];
+    for (user_id, profile) in unresolved {
+        let id = (!profile.is_empty()).then_some(profile.as_str());
+        match zoo.fetch_profile(user_id.get(), id).await {
+            Ok(response) => profiles.push((user_id, profile, response)),
+            // Still down, try again later
+            Err(e) if e.is_transient() => break,
+            Err(e) => {
+                warn!("Failed to backfill profile {:?} for user ID {}: {:?}", id, user_id, e);
+                failed.push((user_id, profile));
+            }
+        }
+    }
+    if profiles.is_empty() && failed.is_empty() {
+        return Ok(());
+    }
+    let mut config = config.write().await;
+    resolve_cooldowns(&mut config, &profiles, &failed, Timestamp::now());
+    save_config(&config).await
+}
+
+/// Fill in the profile of unresolved cooldowns from the fetched profiles, and give up on the ones
+/// that failed for good.
+fn resolve_cooldowns(
+    config: &mut Config,
+    profiles: &[(UserId, String, Arc<ZooProfileResponse>)],
+    failed: &[(UserId, String)],
+    now: Timestamp,
+) {
+    // Won't work next time either, keep them without a profile name
+    for cooldown in &mut config.cooldowns {
+        if cooldown.is_unresolved()
+            && failed.contains(&(cooldown.user_id, cooldown.profile.clone()))
+        {
+            cooldown.profile_source = ProfileSource::Unresolvable;
+        }
+    }
+    for (user_id, profile, response) in profiles {
+        config.known_profiles.entry(*user_id).or_default().remember(response, profile.is_empty());
+        let mut resolved = vec![];
+        config.cooldowns.retain(|cooldown| {
+            if cooldown.user_id == *user_id
+                && cooldown.profile == *profile
+                && cooldown.is_unresolved()
+            {
+                resolved.push(cooldown.clone());
+                return false;
+            }
+            true
+        });
+        for mut cooldown in resolved {
+            if cooldown.profile.is_empty() {
+                // The user may have switched profiles since, only trust the current one for as
+                // long as a known active profile is trusted
+                let recent = cooldown.seen.is_some_and(|seen| {
+                    now.unix_timestamp() - seen.unix_timestamp() <= KnownProfiles::ACTIVE_TTL
+                });
+                if !recent {
+                    cooldown.profile_source = ProfileSource::Unresolvable;
+                    config.cooldowns.push(cooldown);
+                    continue;
+                }
+                cooldown.profile = response.profile_id.clone();
+                cooldown.profile_source = ProfileSource::Api;
+            }
+            cooldown.profile_name = response.name.clone();
+            // Only replaces an older cooldown for the same profile
+            let existing = config.cooldowns.iter().position(|existing| {
+                existing.kind == cooldown.kind
+                    && existing.user_id == cooldown.user_id
+                    && existing.profile == cooldown.profile
+            });
+            if let Some(i) = existing {
+                if config.cooldowns[i].timestamp >= cooldown.timestamp {
+                    continue;
+                }
+                config.cooldowns.remove(i);
+            }
+            info!(
+                "Cooldown profile resolved: {} {} (user {}, profile {})",
+                cooldown.kind, cooldown.timestamp, cooldown.user_id, cooldown.profile
+            );
+            config.cooldowns.push(cooldown);
         }
     }
-    Ok(())
 }
 
 #[tokio::main]
@@ -959,18 +2009,50 @@ async fn main() {
         config.token = std::env::var("DISCORD_TOKEN").expect("missing DISCORD_TOKEN");
     }
     let api_token = config.token.clone();
+    let mut rules_modified = None;
+    if let Err(e) = reload_rules(&mut rules_modified).await {
+        error!("Error loading rules, using built-in rules: {:?}", e);
+    }
     let owners = HashSet::from_iter(config.owners.iter().cloned());
+    let api_config = &config.api;
+    let zoo = Arc::new(
+        ZooClient::builder()
+            .base_url(&api_config.base_url)
+            .cache_ttl(Duration::from_secs(api_config.cache_ttl))
+            .timeout(Duration::from_secs(api_config.timeout))
+            .max_retries(api_config.max_retries)
+            .rate_limit(api_config.requests_per_second)
+            .build(),
+    );
     let config = Arc::new(RwLock::new(config));
     let intents = GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::GUILD_MESSAGE_REACTIONS
         | GatewayIntents::MESSAGE_CONTENT;
-    let reqwest_client = reqwest::Client::new();
 
+    let index = Arc::new(config.read().await.index.build());
     let cloned_config = config.clone();
-    let cloned_reqwest_client = reqwest_client.clone();
+    let cloned_zoo = zoo.clone();
+    let cloned_index = index.clone();
+    let catalog = Arc::new(RwLock::new(Catalog::default()));
+    let cloned_catalog = catalog.clone();
     let framework = Framework::builder()
         .options(FrameworkOptions {
-            commands: vec![botstatus(), cooldowns(), disable(), enable(), find()],
+            commands: vec![
+                botstatus(),
+                cooldowns(),
+                disable(),
+                enable(),
+                hide(),
+                show(),
+                find(),
+                missing(),
+                compare(),
+                trades(),
+                profile(),
+                goals(),
+                goalalert(),
+                quest(),
+            ],
             on_error: |error| {
                 Box::pin(async move {
                     if let Err(e) = on_error(error).await {
@@ -1003,7 +2085,9 @@ async fn main() {
                 Ok(Data {
                     start_time: Timestamp::now(),
                     config: cloned_config,
-                    client: cloned_reqwest_client,
+                    zoo: cloned_zoo,
+                    index: cloned_index,
+                    catalog: cloned_catalog,
                     current_user: ready.user.clone(),
                     shard: ready.shard,
                 })
@@ -1018,7 +2102,7 @@ async fn main() {
     let cloned_token = token.clone();
     let cloned_config = config.clone();
     let cache_http = MyCacheHttp::new(&client);
-    let cloned_reqwest_client = reqwest_client.clone();
+    let cloned_zoo = zoo.clone();
     tracker.spawn(task::spawn(async move {
         let mut interval = time::interval(Duration::from_millis(1000));
         loop {
@@ -1026,7 +2110,7 @@ async fn main() {
                 _ = cloned_token.cancelled() => break,
                 _ = interval.tick() => {},
             }
-            match run_notifications(&cloned_config, &cache_http, &cloned_reqwest_client).await {
+            match run_notifications(&cloned_config, &cache_http, &cloned_zoo).await {
                 Ok(()) => {}
                 Err(e) => {
                     error!("Error running notifications: {:?}", e);
@@ -1035,6 +2119,67 @@ async fn main() {
         }
     }));
 
+    let cloned_token = token.clone();
+    let cloned_config = config.clone();
+    let cache_http = MyCacheHttp::new(&client);
+    let cloned_zoo = zoo.clone();
+    tracker.spawn(task::spawn(async move {
+        let mut interval = time::interval(GOAL_ALERT_INTERVAL);
+        loop {
+            select! {
+                _ = cloned_token.cancelled() => break,
+                _ = interval.tick() => {},
+            }
+            if let Err(e) = run_goal_alerts(&cloned_config, &cache_http, &cloned_zoo).await {
+                error!("Error running goal alerts: {:?}", e);
+            }
+        }
+    }));
+
+    let cloned_token = token.clone();
+    tracker.spawn(task::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(5));
+        loop {
+            select! {
+                _ = cloned_token.cancelled() => break,
+                _ = interval.tick() => {},
+            }
+            if let Err(e) = reload_rules(&mut rules_modified).await {
+                error!("Error reloading rules: {:?}", e);
+            }
+        }
+    }));
+
+    let cloned_token = token.clone();
+    let cloned_config = config.clone();
+    let cloned_zoo = zoo.clone();
+    tracker.spawn(task::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(60));
+        loop {
+            select! {
+                _ = cloned_token.cancelled() => break,
+                _ = interval.tick() => {},
+            }
+            if let Err(e) = backfill_profiles(&cloned_config, &cloned_zoo).await {
+                warn!("Error backfilling profiles: {:?}", e);
+            }
+        }
+    }));
+
+    let cloned_token = token.clone();
+    let cloned_config = config.clone();
+    let cloned_zoo = zoo.clone();
+    tracker.spawn(task::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(60));
+        loop {
+            select! {
+                _ = cloned_token.cancelled() => break,
+                _ = interval.tick() => {},
+            }
+            crawl_profiles(&cloned_config, &index, &catalog, &cloned_zoo).await;
+        }
+    }));
+
     let shard_manager = client.shard_manager.clone();
     let cloned_token = token.clone();
     tokio::spawn(async move {
@@ -1091,3 +2236,333 @@ fn profile_link(name: &str, user_id: UserId, profile: Option<&str>) -> String {
     message.push_named_link_safe(name, format!("<{}>", profile_url(user_id.get(), profile)));
     message.build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockZoo;
+
+    pub(crate) fn cooldown(
+        user_id: u64,
+        profile: &str,
+        kind: CooldownKind,
+        seconds: i64,
+    ) -> Cooldown {
+        let timestamp = Timestamp::now().unix_timestamp() + seconds;
+        Cooldown {
+            kind,
+            channel_id: ChannelId::new(1),
+            user_id: UserId::new(user_id),
+            profile: profile.to_string(),
+            profile_name: format!("{} zoo", profile),
+            profile_source: ProfileSource::Api,
+            timestamp: Timestamp::from_unix_timestamp(timestamp).unwrap(),
+            seen: Some(Timestamp::now()),
+            message_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_animal() {
+        let mock = MockZoo::start().await;
+        let zoo = Arc::new(mock.client());
+        let index = Arc::new(IndexConfig::default().build());
+        let user_ids = [100, 200, 300, 400, 500].map(UserId::new);
+        let catalog = Catalog::default();
+        let cat = parse_query(&catalog, Some("cat"), None, None).unwrap();
+        let message = find_animal(&index.lookup(&user_ids), &cat, Some((0, 5))).unwrap();
+        assert_eq!(message, "Couldn't find **Cat** in 0 profiles.\n_Checking profiles… 0/5 users_");
+
+        let mut tasks = index.spawn_refresh(&zoo, user_ids.to_vec());
+        assert_eq!(tasks.len(), 5);
+        while let Some(result) = tasks.join_next().await {
+            assert!(user_ids.contains(&result.unwrap()));
+        }
+        let message = find_animal(&index.lookup(&user_ids), &cat, None).unwrap();
+        let lines = message.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "Found **Cat** in 3 profiles:");
+        // Has the rare first, pinned last
+        assert!(lines[1].starts_with("- [**Main Zoo**]"), "{}", lines[1]);
+        assert!(lines[1].ends_with(": **5x** 🐱 Cat 🌟"), "{}", lines[1]);
+        assert!(lines[2].starts_with("- [**Single Zoo**]"), "{}", lines[2]);
+        assert!(lines[3].starts_with("- [**Alt Zoo**]"), "{}", lines[3]);
+        assert!(lines[3].ends_with(": **9x** 🐱 Cat 📌"), "{}", lines[3]);
+        assert_eq!(lines[5], "_2 profiles could not be checked (1 private, 1 unavailable)_");
+        assert_eq!(lines[6], "_1 users without a zoo_");
+        assert!(lines[7].starts_with("_Profiles as of <t:"), "{}", lines[7]);
+        assert!(mock.requests().contains(&"/api/profile/100_alt".to_string()));
+
+        // Grouped by profile, most animals first
+        let query = parse_query(&catalog, Some("dog, Cat,lion"), None, None).unwrap();
+        let message = find_animal(&index.lookup(&user_ids), &query, None).unwrap();
+        let lines = message.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "Found **Dog**, **Cat** or **Lion** in 3 profiles:");
+        assert!(
+            lines[1].ends_with(": **5x** 🐱 Cat 🌟, **2x** 🐶 Dog, **1x** 🦁 Lion"),
+            "{}",
+            lines[1]
+        );
+
+        let mut catalog = catalog;
+        catalog.update(index.lookup(&user_ids).profiles.iter().map(|profile| profile.as_ref()));
+        let query = parse_query(&catalog, None, Some("cat"), Some(Rarity::Rare)).unwrap();
+        let message = find_animal(&index.lookup(&user_ids), &query, None).unwrap();
+        assert!(
+            message.starts_with("Found rare animals of the **Cat** family in 1 profiles:\n"),
+            "{}",
+            message
+        );
+        let query = parse_query(&catalog, None, None, Some(Rarity::Common)).unwrap();
+        let message = find_animal(&index.lookup(&user_ids), &query, None).unwrap();
+        assert!(message.starts_with("Found common animals in 3 profiles:"), "{}", message);
+
+        assert_eq!(
+            parse_query(&catalog, Some("cat, girafe"), None, None).unwrap_err(),
+            "**girafe** is not a valid animal. Did you mean **Giraffe**?"
+        );
+        assert_eq!(
+            parse_query(&catalog, None, Some("dgo"), None).unwrap_err(),
+            "**dgo** is not a known family. Did you mean **Dog**?"
+        );
+        assert!(parse_query(&catalog, Some(" , "), None, None).is_err());
+
+        // A slow user doesn't hold up the others
+        mock.delay("/api/profile/500", Duration::from_secs(30));
+        let index = Arc::new(IndexConfig { fetch_timeout: 1, ..Default::default() }.build());
+        let started = Instant::now();
+        index.refresh(&zoo, vec![UserId::new(100), UserId::new(500)]).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let message = find_animal(&index.lookup(&user_ids), &cat, None).unwrap();
+        assert!(message.starts_with("Found **Cat** in 2 profiles:"), "{}", message);
+        assert!(message.contains("\n_1 profiles could not be checked (1 unavailable)_\n"));
+    }
+
+    #[test]
+    fn test_profile_option() {
+        let profile: ZooProfileResponse =
+            serde_json::from_str(include_str!("../fixtures/profiles/100_main.json")).unwrap();
+        let mut config = Config::default();
+        let user_id = UserId::new(100);
+        config.known_profiles.entry(user_id).or_default().remember(&profile, false);
+        assert_eq!(profile_option(&config, user_id, Some("Main Zoo")).as_deref(), Some("main"));
+        assert_eq!(profile_option(&config, user_id, Some("alt")).as_deref(), Some("alt"));
+        assert_eq!(profile_option(&config, user_id, Some(" ")), None);
+    }
+
+    #[tokio::test]
+    async fn test_zoo_client_cache() {
+        let mock = MockZoo::start().await;
+        let zoo =
+            ZooClient::builder().base_url(mock.base_url()).max_retries(0).rate_limit(0.0).build();
+        mock.route("/api/profile/100", 503, "Unavailable");
+        assert!(zoo.fetch_profile(100, None).await.is_err());
+        let stats = zoo.stats();
+        assert_eq!((stats.misses, stats.coalesced, stats.entries), (1, 0, 0));
+
+        // Retried after the error instead of waiting on the failed request
+        mock.route("/api/profile/100", 200, include_str!("../fixtures/profiles/100.json"));
+        let profile = zoo.fetch_profile(100, None).await.unwrap();
+        let stats = zoo.stats();
+        assert_eq!((stats.misses, stats.coalesced, stats.entries), (2, 0, 2));
+        zoo.fetch_profile(100, Some(&profile.profile_id)).await.unwrap();
+        assert_eq!(zoo.stats().hits, 1);
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_zoo_client_coalesced_errors() {
+        let mock = MockZoo::start().await;
+        let zoo =
+            ZooClient::builder().base_url(mock.base_url()).max_retries(1).rate_limit(0.0).build();
+        mock.route("/api/profile/100", 503, "Unavailable");
+        mock.delay("/api/profile/100", Duration::from_millis(100));
+        let fetch = || zoo.fetch_profile(100, None);
+        let results = tokio::join!(fetch(), fetch(), fetch(), fetch(), fetch());
+        let results = [results.0, results.1, results.2, results.3, results.4];
+        assert!(results.iter().all(|result| matches!(result, Err(ZooError::Unavailable(_)))));
+        // One request and its retry, shared by everyone
+        assert_eq!(mock.requests().len(), 2);
+        let stats = zoo.stats();
+        assert_eq!((stats.misses, stats.coalesced, stats.entries), (1, 4, 0));
+    }
+
+    #[tokio::test]
+    async fn test_zoo_client_cache_expiry() {
+        let mock = MockZoo::start().await;
+        let zoo = ZooClient::builder()
+            .base_url(mock.base_url())
+            .cache_ttl(Duration::from_millis(200))
+            .rate_limit(0.0)
+            .build();
+        zoo.fetch_profile(100, None).await.unwrap();
+        zoo.fetch_profile(500, None).await.unwrap();
+        assert_eq!(zoo.stats().entries, 4);
+
+        // Expired profiles are dropped when the next one is cached
+        time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(zoo.stats().entries, 4);
+        zoo.fetch_profile(100, None).await.unwrap();
+        assert_eq!(zoo.stats().entries, 2);
+    }
+
+    #[test]
+    fn test_track_cooldowns() {
+        let mut config = Config::default();
+        let message_id = MessageId::new(1221966238462529637);
+        let rescue = [Cooldown {
+            message_id: Some(message_id),
+            ..cooldown(100, "main", CooldownKind::Rescue, 3600)
+        }];
+        let (updated, react) = config.track_cooldowns(message_id, &rescue);
+        assert_eq!((updated.len(), react), (1, true));
+        // Edits of the message don't react again
+        let (updated, react) = config.track_cooldowns(message_id, &rescue);
+        assert_eq!((updated.len(), react), (0, false));
+        let timestamp = Timestamp::from_unix_timestamp(rescue[0].timestamp.unix_timestamp() + 60);
+        let later = [Cooldown { timestamp: timestamp.unwrap(), ..rescue[0].clone() }];
+        let (updated, react) = config.track_cooldowns(message_id, &later);
+        assert_eq!((updated.len(), react), (1, false));
+        assert_eq!(config.cooldowns[0].timestamp, later[0].timestamp);
+        // A newer message does
+        let newer = MessageId::new(1221966238462529638);
+        let (_, react) = config.track_cooldowns(newer, &rescue);
+        assert!(react);
+
+        // Removed cooldowns aren't added back by edits, only when the user adds them
+        config.dismiss_cooldowns(newer, &rescue, newer.created_at());
+        assert!(config.cooldowns.is_empty());
+        assert!(config.dismissed_messages.contains(&newer));
+        config.track_cooldowns(newer, &rescue);
+        assert!(config.dismissed_messages.is_empty());
+        // Forgotten once the message is old
+        config.dismiss_cooldowns(newer, &rescue, Timestamp::now());
+        assert!(config.dismissed_messages.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_cooldowns() {
+        let profile: ZooProfileResponse =
+            serde_json::from_str(include_str!("../zoo-api/fixtures/profile.json")).unwrap();
+        let (user_id, id) = (UserId::new(100), profile.profile_id.clone());
+        let unresolved = |profile: &str, seconds, seen_ago: i64| Cooldown {
+            profile_name: String::new(),
+            profile_source: ProfileSource::Unresolved,
+            seen: Timestamp::from_unix_timestamp(Timestamp::now().unix_timestamp() - seen_ago).ok(),
+            ..cooldown(100, profile, CooldownKind::Rescue, seconds)
+        };
+        let newer = cooldown(100, &id, CooldownKind::Rescue, 200);
+        let mut config = Config {
+            cooldowns: vec![
+                // Newer than the unresolved one for the same profile
+                newer.clone(),
+                unresolved(&id, 100, 0),
+                // Too old to trust the current profile
+                unresolved("", 300, 2 * KnownProfiles::ACTIVE_TTL),
+                Cooldown { kind: CooldownKind::Quest, ..unresolved("", 400, 0) },
+                Cooldown { user_id: UserId::new(600), ..unresolved("", 500, 0) },
+            ],
+            ..Default::default()
+        };
+        let profiles = [
+            (user_id, String::new(), Arc::new(profile.clone())),
+            (user_id, id.clone(), Arc::new(profile.clone())),
+        ];
+        resolve_cooldowns(
+            &mut config,
+            &profiles,
+            &[(UserId::new(600), String::new())],
+            Timestamp::now(),
+        );
+
+        assert!(config.cooldowns.iter().all(|cooldown| !cooldown.is_unresolved()));
+        let sources = config
+            .cooldowns
+            .iter()
+            .map(|cooldown| (cooldown.kind, cooldown.profile.as_str(), cooldown.profile_source))
+            .collect::<Vec<_>>();
+        assert_eq!(sources, [
+            (CooldownKind::Rescue, id.as_str(), ProfileSource::Api),
+            (CooldownKind::Rescue, "", ProfileSource::Unresolvable),
+            (CooldownKind::Rescue, "", ProfileSource::Unresolvable),
+            (CooldownKind::Quest, id.as_str(), ProfileSource::Api),
+        ]);
+        assert_eq!(config.cooldowns[0].timestamp, newer.timestamp);
+        assert_eq!(config.cooldowns[3].profile_name, profile.name);
+    }
+
+    #[test]
+    fn test_known_profiles() {
+        let profile: ZooProfileResponse =
+            serde_json::from_str(include_str!("../zoo-api/fixtures/profile.json")).unwrap();
+        let mut known = KnownProfiles::default();
+        assert!(known.remember(&profile, true));
+        // Seeing the same active profile again doesn't need saving
+        assert!(!known.remember(&profile, true));
+        assert!(known.recent_active(Timestamp::now()).is_some());
+        let later = Timestamp::from_unix_timestamp(
+            Timestamp::now().unix_timestamp() + KnownProfiles::ACTIVE_TTL + 1,
+        )
+        .unwrap();
+        assert_eq!(known.recent_active(later), None);
+        assert!(known.set_active(None));
+        assert!(!known.remember(&profile, false));
+    }
+
+    #[test]
+    fn test_discoverable_users() {
+        let [a, b, c] = [1, 2, 3].map(UserId::new);
+        let config = Config {
+            hidden_users: BTreeSet::from([b]),
+            channel_users: BTreeMap::from([
+                (ChannelId::new(10), BTreeSet::from([a, b])),
+                (ChannelId::new(20), BTreeSet::from([b, c])),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(config.discoverable_users(ChannelId::new(10)), [a]);
+        assert!(config.discoverable_users(ChannelId::new(30)).is_empty());
+        assert_eq!(config.all_discoverable_users(), BTreeSet::from([a, c]));
+    }
+
+    #[tokio::test]
+    async fn test_collect_notifications() {
+        let mock = MockZoo::start().await;
+        let zoo = mock.client();
+        let mut config = Config {
+            cooldowns: vec![
+                cooldown(100, "alt", CooldownKind::Rescue, -60),
+                cooldown(100, "main", CooldownKind::Quest, -60),
+                cooldown(200, "private", CooldownKind::Rescue, -60),
+                cooldown(400, "broken", CooldownKind::Card, -60),
+                // Too long ago to notify
+                cooldown(500, "zoo", CooldownKind::Rescue, -3600),
+                cooldown(500, "zoo", CooldownKind::Quest, 3600),
+            ],
+            ..Default::default()
+        };
+        let now = Timestamp::now();
+        let user_ids = profile_notified_users(&config, now);
+        assert_eq!(user_ids, [100, 200, 400].map(UserId::new).into());
+        let current_profiles = fetch_current_profiles(&zoo, user_ids).await;
+        let (notifications, changed) = collect_notifications(&mut config, &current_profiles, now);
+        assert!(changed);
+        assert_eq!(config.cooldowns.len(), 1);
+        assert_eq!(config.cooldowns[0].kind, CooldownKind::Quest);
+        assert_eq!(config.known_profiles[&UserId::new(100)].active.as_deref(), Some("main"));
+
+        let contents = notifications
+            .iter()
+            .map(|notification| notification.content.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(contents.len(), 4);
+        assert!(contents[0].starts_with("<@100> 🐾 Rescue cooldown finished for [**alt zoo**]"));
+        assert!(contents[0].contains("Current profile: [**Main Zoo**]"), "{}", contents[0]);
+        assert!(contents[0].contains("/profiles profile:alt"));
+        assert!(contents[1].ends_with("(current profile)"), "{}", contents[1]);
+        // Still notified without the current profile
+        assert!(contents[2].ends_with(")"), "{}", contents[2]);
+        assert!(!contents[2].contains("Current profile"));
+        assert!(!contents[3].contains("Current profile"));
+        assert!(notifications.iter().all(|notification| notification.channel_id.get() == 1));
+    }
+}
@@ -0,0 +1,97 @@
+//! `/missing`: animals missing from a zoo, and how many channel users have them.
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::Result;
+use poise::serenity_prelude::{MessageBuilder, UserId};
+use zoo_api::ZooProfileResponse;
+
+use crate::{catalog::Catalog, index::Lookup, profile_link, push_lookup_status};
+
+/// Most animals listed by `/missing`.
+const MISSING_MAX_ANIMALS: usize = 20;
+
+/// Format the animals missing from the profile, the ones most channel users have first.
+pub fn missing_animals(
+    catalog: &Catalog,
+    profile: &ZooProfileResponse,
+    lookup: &Lookup,
+    rare_only: bool,
+) -> Result<String> {
+    let mut owners = HashMap::<String, BTreeSet<&str>>::new();
+    for other in &lookup.profiles {
+        for animal in other.animals.iter().filter(|animal| animal.amount > 0) {
+            owners.entry(animal.name.to_lowercase()).or_default().insert(&other.user_id);
+        }
+    }
+    let mut missing = catalog
+        .missing(profile)
+        .into_iter()
+        .filter(|animal| !rare_only || animal.rare)
+        .map(|animal| {
+            let count = owners.get(&animal.name.to_lowercase()).map_or(0, BTreeSet::len);
+            (animal, count)
+        })
+        .collect::<Vec<_>>();
+    missing.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.name.cmp(&b.name)));
+
+    let user_id: UserId = profile.user_id.parse()?;
+    let kind = if rare_only { "rare animals" } else { "animals" };
+    let mut message = MessageBuilder::new();
+    message.push(profile_link(&profile.name, user_id, Some(&profile.profile_id)));
+    if missing.is_empty() {
+        message.push(format!(" has all {}!", kind));
+        return Ok(message.build());
+    }
+    message.push_line(format!(" is missing {} {}:", missing.len(), kind));
+    for (animal, count) in missing.iter().take(MISSING_MAX_ANIMALS) {
+        message.push("- ").push_safe(animal.label());
+        if animal.rare && !rare_only {
+            message.push(" (rare)");
+        }
+        match count {
+            0 => message.push_line(": nobody here"),
+            1 => message.push_line(": 1 channel user"),
+            _ => message.push_line(format!(": {} channel users", count)),
+        };
+    }
+    if missing.len() > MISSING_MAX_ANIMALS {
+        message.push_line(format!("... and {} more", missing.len() - MISSING_MAX_ANIMALS));
+    }
+    push_lookup_status(&mut message, lookup, None);
+    Ok(message.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{mock::MockZoo, IndexConfig};
+
+    #[tokio::test]
+    async fn test_missing_animals() {
+        let mock = MockZoo::start().await;
+        let zoo = Arc::new(mock.client());
+        let index = Arc::new(IndexConfig::default().build());
+        index.refresh(&zoo, vec![UserId::new(100), UserId::new(200)]).await;
+        let profile = zoo.fetch_profile(500, None).await.unwrap();
+        let mut catalog = Catalog::default();
+        catalog.update(index.all_profiles().iter().map(AsRef::as_ref));
+        let lookup = index.lookup(&[UserId::new(100), UserId::new(200)]);
+
+        let message = missing_animals(&catalog, &profile, &lookup, false).unwrap();
+        let lines = message.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("[**Single Zoo**]"), "{}", lines[0]);
+        assert!(lines[0].ends_with(" is missing 99 animals:"), "{}", lines[0]);
+        // Both profiles of user 100 count once
+        assert_eq!(lines[1], "- 🐶 Dog: 1 channel user");
+        assert_eq!(lines[2], "- 🦁 Lion (rare): 1 channel user");
+        assert_eq!(lines[3], "- Bactrian Camel (rare): nobody here");
+        assert_eq!(lines[21], "... and 79 more");
+        assert_eq!(lines[23], "_1 profiles could not be checked (1 private)_");
+
+        let message = missing_animals(&catalog, &profile, &lookup, true).unwrap();
+        assert!(message.contains(" is missing 50 rare animals:\n- 🦁 Lion: 1 channel user\n"));
+    }
+}
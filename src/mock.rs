@@ -0,0 +1,120 @@
+//! Local stand-in for the gdcolon.com Zoo API, for tests.
+//!
+//! `GET /api/profile/<user>[_<profile>]` serves `fixtures/profiles/<user>[_<profile>].json`, or a
+//! 404 with the API's error body if there is no such fixture. Routes can be overridden to return
+//! other statuses.
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use zoo_api::ZooClient;
+
+#[derive(Default)]
+struct State {
+    overrides: HashMap<String, (u16, String)>,
+    delays: HashMap<String, Duration>,
+    requests: Vec<String>,
+}
+
+pub struct MockZoo {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockZoo {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(State::default()));
+        let cloned_state = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, cloned_state.clone()));
+            }
+        });
+        MockZoo { addr, state }
+    }
+
+    pub fn base_url(&self) -> String { format!("http://{}", self.addr) }
+
+    /// Client without caching, retry delays or rate limits.
+    pub fn client(&self) -> ZooClient {
+        ZooClient::builder()
+            .base_url(self.base_url())
+            .cache_ttl(Duration::ZERO)
+            .max_retries(0)
+            .rate_limit(0.0)
+            .build()
+    }
+
+    /// Respond to `path` with `status` and `body` instead of the fixture.
+    pub fn route(&self, path: &str, status: u16, body: &str) {
+        self.state.lock().unwrap().overrides.insert(path.to_string(), (status, body.to_string()));
+    }
+
+    /// Wait before responding to `path`.
+    pub fn delay(&self, path: &str, delay: Duration) {
+        self.state.lock().unwrap().delays.insert(path.to_string(), delay);
+    }
+
+    /// Paths requested so far.
+    pub fn requests(&self) -> Vec<String> { self.state.lock().unwrap().requests.clone() }
+}
+
+async fn handle(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let mut buf = vec![0; 4096];
+    let mut len = 0;
+    // Only the request line and headers matter, requests have no body
+    while !buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut buf[len..]).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => len += n,
+        }
+        if len == buf.len() {
+            return;
+        }
+    }
+    let request = String::from_utf8_lossy(&buf[..len]);
+    let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+    let delay = state.lock().unwrap().delays.get(&path).copied();
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+    }
+    let (status, body) = respond(&path, &state);
+    let response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: \
+         close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+fn respond(path: &str, state: &Mutex<State>) -> (u16, String) {
+    let mut state = state.lock().unwrap();
+    state.requests.push(path.to_string());
+    if let Some(response) = state.overrides.get(path) {
+        return response.clone();
+    }
+    let Some(name) = path.strip_prefix("/api/profile/") else {
+        return (404, "Not Found".to_string());
+    };
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures/profiles")
+        .join(format!("{}.json", name.replace(['/', '.'], "")));
+    match std::fs::read_to_string(fixture) {
+        Ok(body) => (200, body),
+        Err(_) => (404, r#"{"error":"This user doesn't have a zoo!"}"#.to_string()),
+    }
+}
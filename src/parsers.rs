@@ -1,200 +1,443 @@
-use std::{ops::Add, time::Duration};
-
-use const_format::concatcp;
+use std::{
+    ops::Add,
+    path::Path,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::{bail, Context as _, Result};
 use once_cell::sync::Lazy;
-use poise::serenity_prelude::{Message, Timestamp};
+use poise::serenity_prelude::{Message, MessageInteractionMetadata, Timestamp, UserId};
 use regex::Regex;
 
-const DURATION_PATTERN: &str = r"(?:(\d)+d \+ )?(?:(\d+):)?(\d+):(\d+)";
-static RESCUE_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(concatcp!(r"another animal in \*\*", DURATION_PATTERN, r"\*\*")).unwrap()
-});
-static RESCUE_MODIFIER_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(concatcp!("finishes in ", DURATION_PATTERN)).unwrap());
-static RESCUE_TODO_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(concatcp!(r"Next Rescue: \*\*", DURATION_PATTERN, r"\*\* \(<t:(\d+)>\)")).unwrap()
-});
-
-static QUEST_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(concatcp!(r"quest will finish in \*\*", DURATION_PATTERN, r"\*\*")).unwrap()
-});
-static QUEST_TODO_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(concatcp!(r"Quest Finishes: \*\*", DURATION_PATTERN, r"\*\* \(<t:(\d+)>\)")).unwrap()
-});
-
-static CARD_TODO_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(concatcp!(r"Next Card Pull: \*\*", DURATION_PATTERN, r"\*\* \(<t:(\d+)>\)")).unwrap()
-});
-
-static MECHANIC_TODO_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(concatcp!(r"Mechanic Finishes: \*\*", DURATION_PATTERN, r"\*\* \(<t:(\d+)>\)"))
-        .unwrap()
-});
-
-static PROFILE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(concatcp!(r"change profiles in ", DURATION_PATTERN)).unwrap());
-
-pub fn extract_rescue_cooldown(message: &Message) -> Option<Timestamp> {
-    if let Some(embed) = message.embeds.first() {
-        // Polar Star cosmetic
-        if let Some(duration) = embed
-            .fields
-            .iter()
-            .find(|field| field.name == "🕓 Cooldown")
-            .and_then(|field| parse_duration(&field.value))
-        {
-            return Some(Timestamp::from(message.timestamp.add(duration)));
+const DURATION_PATTERN: &str =
+    r"(?:(?P<days>\d+)d \+ )?(?:(?P<hours>\d+):)?(?P<minutes>\d+):(?P<seconds>\d+)";
+const TIMESTAMP_PATTERN: &str = r"<t:(?P<timestamp>\d+)(?::\w)?>";
+
+static PROFILE_LINK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"gdcolon\.com/zoo/(?P<user>\d+)_(?P<profile>[\w-]+)").unwrap());
+static PROFILE_SWITCH_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Switched to profile \*\*(?P<name>[^*]+)\*\*").unwrap());
+static PROFILE_FOOTER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Profile: (?P<name>.+?)(?: • |$)").unwrap());
+
+const DEFAULT_RULES: &str = include_str!("rules.toml");
+
+static RULES: Lazy<RwLock<Arc<ParserRules>>> =
+    Lazy::new(|| RwLock::new(Arc::new(ParserRules::default())));
+
+/// An event that Zoo reports a finish time for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Rescue,
+    Quest,
+    Card,
+    Mechanic,
+    Relic,
+    Curse,
+    Profile,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ZooEvent {
+    pub kind: EventKind,
+    pub timestamp: Timestamp,
+}
+
+/// Which part of the message a rule is matched against.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleSource {
+    Content,
+    EmbedDescription,
+    /// Matched against the field name, the time is read from the field value
+    EmbedField,
+    Footer,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleTiming {
+    /// A Discord timestamp, e.g. `<t:1711411463>`
+    Absolute,
+    /// A duration relative to when the message was sent, e.g. `1d + 2:03:04`
+    Relative,
+}
+
+/// The type of message a matching rule classifies as.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleMessage {
+    Todo,
+    Rescue,
+    Modifier,
+    PolarStar,
+    Profile,
+}
+
+impl RuleMessage {
+    /// Whether this type of message is always a response to the user's own command, so a single
+    /// mention in it is the invoking user. `/profile` can show someone else's profile.
+    pub fn is_self_initiated(self) -> bool {
+        match self {
+            RuleMessage::Todo | RuleMessage::Rescue | RuleMessage::Modifier => true,
+            RuleMessage::PolarStar | RuleMessage::Profile => false,
         }
+    }
+}
 
-        // info command
-        if let Some(description) = &embed.description {
-            if let Some(ts) = RESCUE_TODO_RE
-                .captures(description)
-                .and_then(|captures| captures[5].parse().ok())
-                .and_then(|secs| Timestamp::from_unix_timestamp(secs).ok())
-            {
-                return Some(ts);
+#[derive(Debug, Clone, serde::Deserialize)]
+struct RuleDefinition {
+    kind: EventKind,
+    source: RuleSource,
+    pattern: String,
+    timing: RuleTiming,
+    message: RuleMessage,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct RulesFile {
+    /// Only use the rules from this file, not the built-in ones
+    replace_defaults: bool,
+    rules: Vec<RuleDefinition>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub kind: EventKind,
+    pub source: RuleSource,
+    pub timing: RuleTiming,
+    pub message: RuleMessage,
+    regex: Regex,
+}
+
+impl Rule {
+    fn new(definition: RuleDefinition) -> Result<Self> {
+        let RuleDefinition { kind, source, pattern, timing, message } = definition;
+        let allowed = match message {
+            RuleMessage::Todo => true,
+            RuleMessage::Rescue | RuleMessage::PolarStar => {
+                matches!(kind, EventKind::Rescue | EventKind::Quest)
+            }
+            RuleMessage::Modifier => kind == EventKind::Rescue,
+            RuleMessage::Profile => kind == EventKind::Profile,
+        };
+        if !allowed {
+            bail!("Message type {:?} can't contain {:?} events", message, kind);
+        }
+        let expanded = pattern
+            .replace("{duration}", DURATION_PATTERN)
+            .replace("{timestamp}", TIMESTAMP_PATTERN);
+        let regex = Regex::new(&expanded)
+            .with_context(|| format!("Invalid pattern for {:?} rule: {}", kind, pattern))?;
+        // Embed fields read the time from the field value instead
+        if source != RuleSource::EmbedField {
+            let has_group = |name| regex.capture_names().any(|group| group == Some(name));
+            match timing {
+                RuleTiming::Absolute if !has_group("timestamp") => {
+                    bail!("Absolute {:?} rule is missing {{timestamp}}: {}", kind, pattern)
+                }
+                RuleTiming::Relative if !has_group("minutes") || !has_group("seconds") => {
+                    bail!("Relative {:?} rule is missing {{duration}}: {}", kind, pattern)
+                }
+                _ => {}
             }
         }
+        Ok(Rule { kind, source, timing, message, regex })
     }
 
-    // Regular message
-    if let Some(duration) =
-        RESCUE_RE.captures(&message.content).and_then(|captures| parse_duration_captures(captures))
-    {
-        return Some(Timestamp::from(message.timestamp.add(duration)));
+    /// The timestamp found by the rule, and where in its source it was found.
+    fn apply(&self, message: &Message) -> Option<(usize, Timestamp)> {
+        let embed = message.embeds.first();
+        let captures = match self.source {
+            RuleSource::Content => self.regex.captures(&message.content)?,
+            RuleSource::EmbedDescription => self.regex.captures(embed?.description.as_ref()?)?,
+            RuleSource::Footer => self.regex.captures(&embed?.footer.as_ref()?.text)?,
+            RuleSource::EmbedField => {
+                let (position, field) = embed?
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .find(|(_, field)| self.regex.is_match(&field.name))?;
+                let timestamp = match self.timing {
+                    RuleTiming::Absolute => parse_timestamp(&field.value),
+                    RuleTiming::Relative => parse_duration(&field.value)
+                        .map(|duration| relative_timestamp(message, duration)),
+                };
+                return timestamp.map(|timestamp| (position, timestamp));
+            }
+        };
+        let position = captures.get(0).map_or(0, |m| m.start());
+        let timestamp = match self.timing {
+            RuleTiming::Absolute => parse_timestamp_captures(captures),
+            RuleTiming::Relative => parse_duration_captures(captures)
+                .map(|duration| relative_timestamp(message, duration)),
+        };
+        timestamp.map(|timestamp| (position, timestamp))
     }
+}
+
+/// Rules for recognizing cooldowns in Zoo messages, see `rules.toml` for the format.
+#[derive(Debug, Clone)]
+pub struct ParserRules {
+    rules: Vec<Rule>,
+}
 
-    // Terminal `to-do` command
-    if let Some(ts) = RESCUE_TODO_RE
-        .captures(&message.content)
-        .and_then(|captures| captures[5].parse().ok())
-        .and_then(|secs| Timestamp::from_unix_timestamp(secs).ok())
-    {
-        return Some(ts);
+impl ParserRules {
+    pub fn from_toml(s: &str) -> Result<Self> {
+        let file: RulesFile = toml::from_str(s).context("Failed to deserialize rules")?;
+        let mut rules = file
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(i, definition)| {
+                Rule::new(definition).with_context(|| format!("Rule #{}", i + 1))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if !file.replace_defaults {
+            rules.extend(ParserRules::default().rules);
+        }
+        Ok(ParserRules { rules })
     }
 
-    // Cooldown modifier after a rescue
-    if let Some(duration) = RESCUE_MODIFIER_RE
-        .captures(&message.content)
-        .and_then(|captures| parse_duration_captures(captures))
-    {
-        return Some(Timestamp::from(message.timestamp.add(duration)));
+    /// Load rules from a file, with the built-in rules as fallback.
+    pub async fn load(path: &Path) -> Result<Self> {
+        let s = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read rules file {}", path.display()))?;
+        ParserRules::from_toml(&s)
+            .with_context(|| format!("Failed to load rules file {}", path.display()))
     }
 
-    None
+    pub fn len(&self) -> usize { self.rules.len() }
 }
 
-pub fn extract_quest_cooldown(message: &Message) -> Option<Timestamp> {
-    if let Some(embed) = message.embeds.first() {
-        // Polar Star cosmetic
-        if let Some(duration) = embed
-            .fields
-            .iter()
-            .find(|field| field.name == "🌲 Quest ends")
-            .and_then(|field| parse_duration(&field.value))
-        {
-            return Some(Timestamp::from(message.timestamp.add(duration)));
-        }
+impl Default for ParserRules {
+    fn default() -> Self {
+        let file: RulesFile = toml::from_str(DEFAULT_RULES).expect("Invalid built-in rules");
+        let rules = file
+            .rules
+            .into_iter()
+            .map(|definition| Rule::new(definition).expect("Invalid built-in rule"))
+            .collect();
+        ParserRules { rules }
+    }
+}
+
+/// The rules currently used by [`ZooMessage::parse`].
+pub fn rules() -> Arc<ParserRules> { RULES.read().unwrap().clone() }
+
+/// Replace the rules used by [`ZooMessage::parse`].
+pub fn set_rules(rules: ParserRules) { *RULES.write().unwrap() = Arc::new(rules); }
+
+/// The result of parsing a Zoo message once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZooMessage {
+    /// Terminal `to-do` command or `/info` embed, listing every upcoming event
+    TodoSnapshot(Vec<ZooEvent>),
+    /// Response to a rescue
+    RescueResult {
+        rescue: Option<Timestamp>,
+        quest: Option<Timestamp>,
+    },
+    /// Cooldown raised or lowered after a rescue
+    CooldownModifier {
+        rescue: Timestamp,
+    },
+    /// Polar Star cosmetic
+    PolarStarEmbed {
+        rescue: Option<Timestamp>,
+        quest: Option<Timestamp>,
+    },
+    /// `/profile` command
+    ProfileEmbed {
+        profile: Timestamp,
+    },
+    Unknown,
+}
+
+impl ZooMessage {
+    pub fn parse(message: &Message) -> Self { ZooMessage::parse_with(message, &rules()) }
 
-        // info command
-        if let Some(description) = &embed.description {
-            if let Some(ts) = QUEST_TODO_RE
-                .captures(description)
-                .and_then(|captures| captures[5].parse().ok())
-                .and_then(|secs| Timestamp::from_unix_timestamp(secs).ok())
+    pub fn parse_with(message: &Message, rules: &ParserRules) -> Self {
+        let mut kind = None;
+        let mut matches = Vec::<(usize, ZooEvent)>::new();
+        for rule in &rules.rules {
+            if kind.is_some_and(|kind| kind != rule.message)
+                || matches.iter().any(|(_, event)| event.kind == rule.kind)
             {
-                return Some(ts);
+                continue;
             }
+            if let Some((position, timestamp)) = rule.apply(message) {
+                kind = Some(rule.message);
+                matches.push((position, ZooEvent { kind: rule.kind, timestamp }));
+            }
+        }
+        // Keep the events in the order they appear in the message, not the order of the rules
+        matches.sort_by_key(|(position, _)| *position);
+        let events = matches.into_iter().map(|(_, event)| event).collect::<Vec<_>>();
+        let find = |kind| events.iter().find(|event| event.kind == kind).map(|e| e.timestamp);
+        match kind {
+            Some(RuleMessage::Todo) => ZooMessage::TodoSnapshot(events),
+            Some(RuleMessage::Rescue) => ZooMessage::RescueResult {
+                rescue: find(EventKind::Rescue),
+                quest: find(EventKind::Quest),
+            },
+            Some(RuleMessage::Modifier) => match find(EventKind::Rescue) {
+                Some(rescue) => ZooMessage::CooldownModifier { rescue },
+                None => ZooMessage::Unknown,
+            },
+            Some(RuleMessage::PolarStar) => ZooMessage::PolarStarEmbed {
+                rescue: find(EventKind::Rescue),
+                quest: find(EventKind::Quest),
+            },
+            Some(RuleMessage::Profile) => match find(EventKind::Profile) {
+                Some(profile) => ZooMessage::ProfileEmbed { profile },
+                None => ZooMessage::Unknown,
+            },
+            None => ZooMessage::Unknown,
         }
     }
 
-    // Regular message
-    if let Some(duration) =
-        QUEST_RE.captures(&message.content).and_then(|captures| parse_duration_captures(captures))
-    {
-        return Some(Timestamp::from(message.timestamp.add(duration)));
+    /// The type of message, or `None` if it's unknown.
+    pub fn kind(&self) -> Option<RuleMessage> {
+        match self {
+            ZooMessage::TodoSnapshot(_) => Some(RuleMessage::Todo),
+            ZooMessage::RescueResult { .. } => Some(RuleMessage::Rescue),
+            ZooMessage::CooldownModifier { .. } => Some(RuleMessage::Modifier),
+            ZooMessage::PolarStarEmbed { .. } => Some(RuleMessage::PolarStar),
+            ZooMessage::ProfileEmbed { .. } => Some(RuleMessage::Profile),
+            ZooMessage::Unknown => None,
+        }
     }
 
-    // Terminal `to-do` command
-    if let Some(ts) = QUEST_TODO_RE
-        .captures(&message.content)
-        .and_then(|captures| captures[5].parse().ok())
-        .and_then(|secs| Timestamp::from_unix_timestamp(secs).ok())
-    {
-        return Some(ts);
+    /// All events found in the message, with absolute timestamps.
+    pub fn events(&self) -> Vec<ZooEvent> {
+        let event = |kind, timestamp: Option<Timestamp>| {
+            timestamp.map(|timestamp| ZooEvent { kind, timestamp })
+        };
+        match *self {
+            ZooMessage::TodoSnapshot(ref events) => events.clone(),
+            ZooMessage::RescueResult { rescue, quest }
+            | ZooMessage::PolarStarEmbed { rescue, quest } => {
+                [event(EventKind::Rescue, rescue), event(EventKind::Quest, quest)]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            }
+            ZooMessage::CooldownModifier { rescue } => {
+                vec![ZooEvent { kind: EventKind::Rescue, timestamp: rescue }]
+            }
+            ZooMessage::ProfileEmbed { profile } => {
+                vec![ZooEvent { kind: EventKind::Profile, timestamp: profile }]
+            }
+            ZooMessage::Unknown => vec![],
+        }
     }
+}
 
-    None
+/// How the user who triggered a Zoo message was found.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvokerSource {
+    InteractionMetadata,
+    /// Deprecated `interaction` field
+    Interaction,
+    /// Prefix-style command, Zoo replies to the user's message
+    Reply,
+    Mention,
 }
 
-pub fn extract_card_cooldown(message: &Message) -> Option<Timestamp> {
-    if let Some(embed) = message.embeds.first() {
-        // info command
-        if let Some(description) = &embed.description {
-            if let Some(ts) = CARD_TODO_RE
-                .captures(description)
-                .and_then(|captures| captures[5].parse().ok())
-                .and_then(|secs| Timestamp::from_unix_timestamp(secs).ok())
-            {
-                return Some(ts);
-            }
+/// Find the user who triggered a Zoo message, `parsed` from the same message.
+pub fn invoking_user(message: &Message, parsed: &ZooMessage) -> Option<(UserId, InvokerSource)> {
+    if let Some(metadata) = message.interaction_metadata.as_deref() {
+        let user = match metadata {
+            MessageInteractionMetadata::Command(metadata) => Some(&metadata.user),
+            MessageInteractionMetadata::Component(metadata) => Some(&metadata.user),
+            MessageInteractionMetadata::ModalSubmit(metadata) => Some(&metadata.user),
+            _ => None,
+        };
+        if let Some(user) = user {
+            return Some((user.id, InvokerSource::InteractionMetadata));
         }
     }
-
-    // Terminal `to-do` command
-    if let Some(ts) = CARD_TODO_RE
-        .captures(&message.content)
-        .and_then(|captures| captures[5].parse().ok())
-        .and_then(|secs| Timestamp::from_unix_timestamp(secs).ok())
-    {
-        return Some(ts);
+    #[allow(deprecated)]
+    if let Some(interaction) = message.interaction.as_deref() {
+        return Some((interaction.user.id, InvokerSource::Interaction));
+    }
+    if let Some(referenced) = message.referenced_message.as_deref() {
+        if !referenced.author.bot {
+            return Some((referenced.author.id, InvokerSource::Reply));
+        }
+    }
+    // Only if it's unambiguous, gifts and trades mention other users too
+    if !parsed.kind().is_some_and(RuleMessage::is_self_initiated) {
+        return None;
+    }
+    let mut mentions = message.mentions.iter().filter(|user| !user.bot);
+    if let (Some(user), None) = (mentions.next(), mentions.next()) {
+        return Some((user.id, InvokerSource::Mention));
     }
-
     None
 }
 
-pub fn extract_mechanic_cooldown(message: &Message) -> Option<Timestamp> {
-    if let Some(embed) = message.embeds.first() {
-        // info command
-        if let Some(description) = &embed.description {
-            if let Some(ts) = MECHANIC_TODO_RE
-                .captures(description)
-                .and_then(|captures| captures[5].parse().ok())
-                .and_then(|secs| Timestamp::from_unix_timestamp(secs).ok())
-            {
-                return Some(ts);
+/// What a Zoo message tells about which profile it's for.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileEvidence {
+    /// Profile ID from a gdcolon.com link
+    Id(String),
+    /// Zoo name
+    Name(String),
+    /// Zoo name after switching profiles with `/profiles`
+    Switched(String),
+}
+
+/// Find which profile a Zoo message is for, from links, a profile switch or the embed footer, in
+/// that order.
+pub fn profile_evidence(message: &Message, user_id: UserId) -> Option<ProfileEvidence> {
+    let embed = message.embeds.first();
+    let links = embed
+        .into_iter()
+        .flat_map(|embed| {
+            [embed.url.as_deref(), embed.author.as_ref().and_then(|a| a.url.as_deref())]
+        })
+        .flatten();
+    for link in links {
+        if let Some(captures) = PROFILE_LINK_RE.captures(link) {
+            if captures["user"].parse() == Ok(user_id.get()) {
+                return Some(ProfileEvidence::Id(captures["profile"].to_string()));
             }
         }
     }
-
-    // Terminal `to-do` command
-    if let Some(ts) = MECHANIC_TODO_RE
-        .captures(&message.content)
-        .and_then(|captures| captures[5].parse().ok())
-        .and_then(|secs| Timestamp::from_unix_timestamp(secs).ok())
-    {
-        return Some(ts);
+    let description = embed.and_then(|embed| embed.description.as_deref());
+    for text in [Some(message.content.as_str()), description].into_iter().flatten() {
+        if let Some(captures) = PROFILE_SWITCH_RE.captures(text) {
+            return Some(ProfileEvidence::Switched(captures["name"].to_string()));
+        }
     }
+    // The embed author is the Discord display name, not the Zoo name
+    let captures = PROFILE_FOOTER_RE.captures(&embed?.footer.as_ref()?.text)?;
+    Some(ProfileEvidence::Name(captures["name"].to_string()))
+}
 
-    None
+/// Durations in an edited message count from the edit, e.g. a confirmation that turns into a
+/// rescue result.
+fn relative_timestamp(message: &Message, duration: Duration) -> Timestamp {
+    let sent = message.edited_timestamp.unwrap_or(message.timestamp);
+    Timestamp::from(sent.add(duration))
 }
 
-pub fn extract_profile_cooldown(message: &Message) -> Option<Timestamp> {
-    if let Some(embed) = message.embeds.first() {
-        // profile command
-        // TODO can't construct an EmbedFooter to test
-        if let Some(footer) = &embed.footer {
-            if let Some(ts) = PROFILE_RE
-                .captures(&footer.text)
-                .and_then(|captures| parse_duration_captures(captures))
-            {
-                return Some(Timestamp::from(message.timestamp.add(ts)));
-            }
-        }
-    }
+fn parse_timestamp(s: &str) -> Option<Timestamp> {
+    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(TIMESTAMP_PATTERN).unwrap());
+    parse_timestamp_captures(RE.captures(s)?)
+}
 
-    None
+fn parse_timestamp_captures(captures: regex::Captures) -> Option<Timestamp> {
+    let secs = captures.name("timestamp")?.as_str().parse().ok()?;
+    Timestamp::from_unix_timestamp(secs).ok()
 }
 
 pub fn parse_duration(s: &str) -> Option<Duration> {
@@ -203,10 +446,10 @@ pub fn parse_duration(s: &str) -> Option<Duration> {
 }
 
 pub fn parse_duration_captures(captures: regex::Captures) -> Option<Duration> {
-    let days: u64 = captures.get(1).and_then(|s| s.as_str().parse().ok()).unwrap_or(0);
-    let hours: u64 = captures.get(2).and_then(|s| s.as_str().parse().ok()).unwrap_or(0);
-    let minutes: u64 = captures[3].parse().ok()?;
-    let seconds: u64 = captures[4].parse().ok()?;
+    let days: u64 = captures.name("days").and_then(|s| s.as_str().parse().ok()).unwrap_or(0);
+    let hours: u64 = captures.name("hours").and_then(|s| s.as_str().parse().ok()).unwrap_or(0);
+    let minutes: u64 = captures.name("minutes")?.as_str().parse().ok()?;
+    let seconds: u64 = captures.name("seconds")?.as_str().parse().ok()?;
     Some(Duration::from_secs(days * 86400 + hours * 3600 + minutes * 60 + seconds))
 }
 
@@ -217,6 +460,89 @@ mod tests {
     use super::*;
     use crate::ZOO_USER_ID;
 
+    const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/messages");
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct ExpectedEvent {
+        kind: EventKind,
+        timestamp: i64,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct ExpectedInvoker {
+        user: UserId,
+        source: InvokerSource,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Expected {
+        message: Option<RuleMessage>,
+        events: Vec<ExpectedEvent>,
+        invoker: Option<ExpectedInvoker>,
+        profile: Option<ProfileEvidence>,
+    }
+
+    impl Expected {
+        fn from_message(message: &Message) -> Self {
+            let parsed = ZooMessage::parse(message);
+            let kind = parsed.kind();
+            let events = parsed
+                .events()
+                .into_iter()
+                .map(|event| ExpectedEvent {
+                    kind: event.kind,
+                    timestamp: event.timestamp.unix_timestamp(),
+                })
+                .collect();
+            let invoker = invoking_user(message, &parsed)
+                .map(|(user, source)| ExpectedInvoker { user, source });
+            let profile =
+                invoker.as_ref().and_then(|invoker| profile_evidence(message, invoker.user));
+            Expected { message: kind, events, invoker, profile }
+        }
+    }
+
+    fn extract(message: &Message, kind: EventKind) -> Option<Timestamp> {
+        ZooMessage::parse(message)
+            .events()
+            .into_iter()
+            .find(|event| event.kind == kind)
+            .map(|event| event.timestamp)
+    }
+
+    /// Every `<name>.json` in the fixtures directory is a recorded Zoo message,
+    /// with the expected parse result in `<name>.expected.json`.
+    #[test]
+    fn test_fixtures() {
+        let mut count = 0;
+        let mut failures = vec![];
+        for entry in std::fs::read_dir(FIXTURES_DIR).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            let Some(stem) = name.strip_suffix(".json") else {
+                continue;
+            };
+            if stem.ends_with(".expected") {
+                continue;
+            }
+            let message: Message = serde_json::from_str(&std::fs::read_to_string(&path).unwrap())
+                .unwrap_or_else(|e| panic!("Failed to deserialize fixture {}: {}", name, e));
+            let expected_path = path.with_file_name(format!("{}.expected.json", stem));
+            let expected: Expected =
+                serde_json::from_str(&std::fs::read_to_string(&expected_path).unwrap())
+                    .unwrap_or_else(|e| panic!("Failed to deserialize {}: {}", name, e));
+            assert_eq!(message.author.id, ZOO_USER_ID, "{} is not a Zoo message", name);
+            let actual = Expected::from_message(&message);
+            if actual != expected {
+                failures
+                    .push(format!("{}:\n  expected {:?}\n  actual   {:?}", stem, expected, actual));
+            }
+            count += 1;
+        }
+        assert!(count > 0, "No fixtures found in {}", FIXTURES_DIR);
+        assert!(failures.is_empty(), "Fixtures failed:\n{}", failures.join("\n"));
+    }
+
     #[test]
     fn test_parse_duration() {
         assert_eq!(
@@ -235,7 +561,7 @@ mod tests {
         embed.fields.push(EmbedField::new("🕓 Cooldown", "1d + 2:03:04", false));
         message.embeds.push(embed);
         assert_eq!(
-            extract_rescue_cooldown(&message),
+            extract(&message, EventKind::Rescue),
             Some(Timestamp::from_unix_timestamp(86400 + 2 * 3600 + 3 * 60 + 4).unwrap())
         );
     }
@@ -246,7 +572,7 @@ mod tests {
         message.author.id = ZOO_USER_ID;
         message.content = r"🪆 **User**, you can rescue another animal in **54:14**. Your quest will finish in **2.5 days**.".to_string();
         assert_eq!(
-            extract_rescue_cooldown(&message),
+            extract(&message, EventKind::Rescue),
             Some(Timestamp::from_unix_timestamp(54 * 60 + 14).unwrap())
         );
     }
@@ -262,7 +588,7 @@ __**Upcoming Events**__
 > 🏕️ Quest Finishes: **2d + 06:41:20** (<t:1711597212>)"
             .to_string();
         assert_eq!(
-            extract_rescue_cooldown(&message),
+            extract(&message, EventKind::Rescue),
             Some(Timestamp::from_unix_timestamp(1711411463).unwrap())
         );
     }
@@ -276,7 +602,7 @@ __**Upcoming Events**__
 <:energy_drink:979087891240210492> Cooldown raised by **44 minutes**! (finishes in 6:44:57)"
             .to_string();
         assert_eq!(
-            extract_rescue_cooldown(&message),
+            extract(&message, EventKind::Rescue),
             Some(Timestamp::from_unix_timestamp(6 * 3600 + 44 * 60 + 57).unwrap())
         );
     }
@@ -289,7 +615,7 @@ __**Upcoming Events**__
         embed.fields.push(EmbedField::new("🌲 Quest ends", "1d + 2:03:04", false));
         message.embeds.push(embed);
         assert_eq!(
-            extract_quest_cooldown(&message),
+            extract(&message, EventKind::Quest),
             Some(Timestamp::from_unix_timestamp(86400 + 2 * 3600 + 3 * 60 + 4).unwrap())
         );
     }
@@ -300,7 +626,7 @@ __**Upcoming Events**__
         message.author.id = ZOO_USER_ID;
         message.content = r"**User**, you can rescue another animal in **4:43:02**. Your quest will finish in **3:16:57**.".to_string();
         assert_eq!(
-            extract_quest_cooldown(&message),
+            extract(&message, EventKind::Quest),
             Some(Timestamp::from_unix_timestamp(3 * 3600 + 16 * 60 + 57).unwrap())
         );
     }
@@ -316,7 +642,7 @@ __**Upcoming Events**__
 > 🏕️ Quest Finishes: **2d + 06:41:20** (<t:1711597212>)"
             .to_string();
         assert_eq!(
-            extract_quest_cooldown(&message),
+            extract(&message, EventKind::Quest),
             Some(Timestamp::from_unix_timestamp(1711597212).unwrap())
         );
     }
@@ -332,7 +658,7 @@ __**Upcoming Events**__
 > 🏕️ Quest Finishes: **2d + 06:41:20** (<t:1711597212>)"
             .to_string();
         assert_eq!(
-            extract_card_cooldown(&message),
+            extract(&message, EventKind::Card),
             Some(Timestamp::from_unix_timestamp(1711431269).unwrap())
         );
     }
@@ -350,7 +676,7 @@ __**Upcoming Events**__
         );
         message.embeds.push(embed);
         assert_eq!(
-            extract_rescue_cooldown(&message),
+            extract(&message, EventKind::Rescue),
             Some(Timestamp::from_unix_timestamp(1711424648).unwrap())
         );
     }
@@ -368,7 +694,7 @@ __**Upcoming Events**__
         );
         message.embeds.push(embed);
         assert_eq!(
-            extract_quest_cooldown(&message),
+            extract(&message, EventKind::Quest),
             Some(Timestamp::from_unix_timestamp(1711573810).unwrap())
         );
     }
@@ -386,7 +712,7 @@ __**Upcoming Events**__
         );
         message.embeds.push(embed);
         assert_eq!(
-            extract_card_cooldown(&message),
+            extract(&message, EventKind::Card),
             Some(Timestamp::from_unix_timestamp(1711437242).unwrap())
         );
     }
@@ -409,8 +735,149 @@ __**Upcoming Events**__
         );
         message.embeds.push(embed);
         assert_eq!(
-            extract_mechanic_cooldown(&message),
+            extract(&message, EventKind::Mechanic),
             Some(Timestamp::from_unix_timestamp(1711583100).unwrap())
         );
     }
+
+    #[test]
+    fn test_parse_zoo_message_todo() {
+        let mut message = Message::default();
+        message.author.id = ZOO_USER_ID;
+        message.content = r"`$ td`
+__**Upcoming Events**__
+> 🎴 Next Card Pull: **2:22:54** (<t:1711560217>)
+> 🐾 Next Rescue: **4:18:33** (<t:1711567155>)
+> 🎒 Mechanic Finishes: **8:44:18** (<t:1711583100>)
+> 💻 Relic Cooldown: **11:58:52** (<t:1711594774>)
+> 🏕️ Quest Finishes: **4d + 05:51:06** (<t:1711918308>)
+> 💀 Curse Expires: **13d + 15:10:10** (<t:1712729452>)"
+            .to_string();
+        let ts = |secs| Timestamp::from_unix_timestamp(secs).unwrap();
+        assert_eq!(
+            ZooMessage::parse(&message),
+            ZooMessage::TodoSnapshot(vec![
+                ZooEvent { kind: EventKind::Card, timestamp: ts(1711560217) },
+                ZooEvent { kind: EventKind::Rescue, timestamp: ts(1711567155) },
+                ZooEvent { kind: EventKind::Mechanic, timestamp: ts(1711583100) },
+                ZooEvent { kind: EventKind::Relic, timestamp: ts(1711594774) },
+                ZooEvent { kind: EventKind::Quest, timestamp: ts(1711918308) },
+                ZooEvent { kind: EventKind::Curse, timestamp: ts(1712729452) },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_zoo_message_rescue_result() {
+        let mut message = Message::default();
+        message.author.id = ZOO_USER_ID;
+        message.content = r"**User**, you can rescue another animal in **4:43:02**. Your quest will finish in **3:16:57**.".to_string();
+        assert_eq!(ZooMessage::parse(&message), ZooMessage::RescueResult {
+            rescue: Some(Timestamp::from_unix_timestamp(4 * 3600 + 43 * 60 + 2).unwrap()),
+            quest: Some(Timestamp::from_unix_timestamp(3 * 3600 + 16 * 60 + 57).unwrap()),
+        });
+    }
+
+    #[test]
+    fn test_parse_zoo_message_modifier() {
+        let mut message = Message::default();
+        message.author.id = ZOO_USER_ID;
+        message.content = r"`$ z`
+🐂🐂 You brought home a pair of **Oxen**! Lucky you!
+<:energy_drink:979087891240210492> Cooldown raised by **44 minutes**! (finishes in 6:44:57)"
+            .to_string();
+        assert_eq!(ZooMessage::parse(&message), ZooMessage::CooldownModifier {
+            rescue: Timestamp::from_unix_timestamp(6 * 3600 + 44 * 60 + 57).unwrap()
+        });
+    }
+
+    #[test]
+    fn test_parse_zoo_message_polar_star() {
+        let mut message = Message::default();
+        message.author.id = ZOO_USER_ID;
+        let mut embed = Embed::default();
+        embed.fields.push(EmbedField::new("🕓 Cooldown", "1:00", false));
+        embed.fields.push(EmbedField::new("🌲 Quest ends", "1d + 2:03:04", false));
+        message.embeds.push(embed);
+        assert_eq!(ZooMessage::parse(&message), ZooMessage::PolarStarEmbed {
+            rescue: Some(Timestamp::from_unix_timestamp(60).unwrap()),
+            quest: Some(Timestamp::from_unix_timestamp(86400 + 2 * 3600 + 3 * 60 + 4).unwrap()),
+        });
+    }
+
+    #[test]
+    fn test_parse_zoo_message_unknown() {
+        let mut message = Message::default();
+        message.author.id = ZOO_USER_ID;
+        message.content = "🎉 Nothing to see here.".to_string();
+        assert_eq!(ZooMessage::parse(&message), ZooMessage::Unknown);
+        assert!(ZooMessage::parse(&message).events().is_empty());
+    }
+
+    #[test]
+    fn test_rules_custom() {
+        let rules = ParserRules::from_toml(
+            r#"
+            [[rules]]
+            kind = "card"
+            source = "content"
+            pattern = 'pull another card {timestamp}'
+            timing = "absolute"
+            message = "todo"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rules.len(), ParserRules::default().len() + 1);
+
+        let mut message = Message::default();
+        message.author.id = ZOO_USER_ID;
+        message.content = "You can pull another card <t:1711431269:R>".to_string();
+        assert_eq!(
+            ZooMessage::parse_with(&message, &rules),
+            ZooMessage::TodoSnapshot(vec![ZooEvent {
+                kind: EventKind::Card,
+                timestamp: Timestamp::from_unix_timestamp(1711431269).unwrap()
+            }])
+        );
+        assert_eq!(ZooMessage::parse_with(&message, &ParserRules::default()), ZooMessage::Unknown);
+    }
+
+    #[test]
+    fn test_rules_replace_defaults() {
+        let rules = ParserRules::from_toml(
+            r#"
+            replace_defaults = true
+
+            [[rules]]
+            kind = "rescue"
+            source = "content"
+            pattern = 'rescue again in {duration}'
+            timing = "relative"
+            message = "rescue"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rules.len(), 1);
+    }
+
+    #[test]
+    fn test_rules_invalid() {
+        let rule = |kind: &str, pattern: &str, timing: &str, message: &str| {
+            ParserRules::from_toml(&format!(
+                "[[rules]]\nkind = '{}'\nsource = 'content'\npattern = '{}'\ntiming = '{}'\nmessage = '{}'",
+                kind, pattern, timing, message
+            ))
+        };
+        assert!(rule("rescue", "in {duration}", "relative", "rescue").is_ok());
+        // Invalid regex
+        assert!(rule("rescue", "in ({duration}", "relative", "rescue").is_err());
+        // Missing time
+        assert!(rule("rescue", "in {duration}", "absolute", "todo").is_err());
+        assert!(rule("rescue", "at {timestamp}", "relative", "todo").is_err());
+        // Event can't be part of that message
+        assert!(rule("card", "in {duration}", "relative", "rescue").is_err());
+        assert!(rule("quest", "in {duration}", "relative", "profile").is_err());
+        // Unknown kind
+        assert!(rule("nap", "in {duration}", "relative", "todo").is_err());
+    }
 }
@@ -0,0 +1,214 @@
+//! `/profile`: a summary embed of a zoo, with a menu to switch between the user's profiles.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use poise::serenity_prelude::{
+    CreateActionRow, CreateEmbed, CreateEmbedFooter, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption, MessageBuilder, User, UserId,
+};
+use zoo_api::{profile_url, ZooProfileActiveQuest, ZooProfileResponse};
+
+use crate::{remember_profile, Data};
+
+/// Fetch a profile for `/profile`, and create the embed and the menu to switch profiles. Returns
+/// an error message if the profile can't be shown.
+pub async fn create_profile_message(
+    data: &Data,
+    viewer: UserId,
+    user: &User,
+    profile: Option<&str>,
+) -> Result<Result<(CreateEmbed, Vec<CreateActionRow>), String>> {
+    let fetched = match data.zoo.fetch_profile(user.id.get(), profile).await {
+        // Private profiles are only shown to their owner
+        Ok(fetched) if fetched.private && user.id != viewer => {
+            let message = MessageBuilder::new()
+                .push("The profile of ")
+                .user(user)
+                .push(" is private.")
+                .build();
+            return Ok(Err(message));
+        }
+        Ok(fetched) => fetched,
+        Err(e) => {
+            let message = MessageBuilder::new()
+                .push("Couldn't fetch the profile of ")
+                .user(user)
+                .push(format!(": {}", e))
+                .build();
+            return Ok(Err(message));
+        }
+    };
+    remember_profile(data, user.id, &fetched, profile.is_none()).await?;
+    let mut names = data
+        .config
+        .read()
+        .await
+        .known_profiles
+        .get(&user.id)
+        .map(|known| known.names.clone())
+        .unwrap_or_default();
+    for indexed in data.index.lookup(&[user.id]).profiles {
+        names.entry(indexed.profile_id.clone()).or_insert(indexed.name.clone());
+    }
+    let embed = profile_embed(&fetched, Some(user.face()))?;
+    let components = profile_menu(&fetched, &names).into_iter().collect();
+    Ok(Ok((embed, components)))
+}
+
+fn profile_embed(profile: &ZooProfileResponse, avatar: Option<String>) -> Result<CreateEmbed> {
+    let user_id: UserId = profile.user_id.parse()?;
+    let mut embed = CreateEmbed::new()
+        .title(&profile.name)
+        .url(profile_url(user_id.get(), Some(&profile.profile_id)));
+    if let Some(avatar) = profile.user.avatar.clone().or(avatar) {
+        embed = embed.thumbnail(avatar);
+    }
+    let color = profile
+        .color
+        .as_deref()
+        .and_then(|color| u32::from_str_radix(color.trim_start_matches('#'), 16).ok());
+    if let Some(color) = color.filter(|_| !profile.settings.disable_custom_color) {
+        embed = embed.colour(color);
+    }
+
+    let relics = profile
+        .equipped_relics
+        .iter()
+        .map(|name| match profile.relics.iter().find(|relic| &relic.name == name) {
+            Some(relic) if !relic.emoji.is_empty() => format!("{} {}", relic.emoji, name),
+            _ => name.clone(),
+        })
+        .collect::<Vec<_>>();
+    let cosmetic = profile.equipped_cosmetic.as_ref().map(|name| {
+        match profile.cosmetics.iter().find(|cosmetic| &cosmetic.name == name) {
+            Some(cosmetic) if !cosmetic.emoji.is_empty() => format!("{} {}", cosmetic.emoji, name),
+            _ => name.clone(),
+        }
+    });
+    let leader = profile.equipped_leader.as_ref().map(|name| {
+        match profile.leaders.iter().find(|leader| &leader.name == name) {
+            Some(leader) => {
+                format!("{} {} (level {})", leader.emoji, name, leader.level).trim().to_string()
+            }
+            None => name.clone(),
+        }
+    });
+    let none = || "None".to_string();
+    embed = embed
+        .field("Score", profile.score.to_string(), true)
+        .field("Completion", format!("{:.1}%", profile.completion), true)
+        .field(
+            "Animals",
+            format!(
+                "{} unique ({} rare)",
+                profile.unique_animals.total, profile.unique_animals.rare
+            ),
+            true,
+        )
+        .field("Relics", if relics.is_empty() { none() } else { relics.join(", ") }, true)
+        .field("Cosmetic", cosmetic.unwrap_or_else(none), true)
+        .field("Leader", leader.unwrap_or_else(none), true);
+    if let Some(quest) = &profile.quest {
+        embed = embed.field("Quest", format_quest(quest), false);
+    }
+    if let Some(curse) = &profile.curse {
+        let mut value = curse.name.clone();
+        if curse.weak {
+            value.push_str(" (weak)");
+        }
+        for effect in [&curse.effects.kind, &curse.effects.cure] {
+            if !effect.description.is_empty() {
+                value.push_str(&format!("\n{}: {}", effect.name, effect.description));
+            }
+        }
+        embed = embed.field("Curse", value, false);
+    }
+    if profile.profiles.len() > 1 {
+        let position = profile.profiles.iter().position(|id| id == &profile.profile_id);
+        let position = position.map_or("?".to_string(), |i| (i + 1).to_string());
+        embed = embed.footer(CreateEmbedFooter::new(format!(
+            "Profile {} of {}",
+            position,
+            profile.profiles.len()
+        )));
+    }
+    Ok(embed)
+}
+
+/// E.g. "Rescue a **Cat**" or "Rescue any **Cat** family animal".
+pub fn format_quest(quest: &ZooProfileActiveQuest) -> String {
+    let mut message = MessageBuilder::new();
+    let mut kind = quest.kind.chars();
+    message.push(
+        kind.next().map(|c| c.to_uppercase().chain(kind).collect::<String>()).unwrap_or_default(),
+    );
+    if !quest.animal.is_empty() {
+        message.push(" a ").push_bold_safe(&quest.animal);
+    } else if !quest.family.is_empty() {
+        message.push(" any ").push_bold_safe(&quest.family).push(" family animal");
+    }
+    message.build()
+}
+
+/// Menu to switch between the user's profiles, if there is more than one. `names` are profile
+/// names by ID.
+fn profile_menu(
+    profile: &ZooProfileResponse,
+    names: &BTreeMap<String, String>,
+) -> Option<CreateActionRow> {
+    if profile.profiles.len() < 2 {
+        return None;
+    }
+    let options = profile
+        .profiles
+        .iter()
+        // Most options Discord allows
+        .take(25)
+        .map(|id| {
+            let label = names.get(id).unwrap_or(id);
+            CreateSelectMenuOption::new(label, id).default_selection(id == &profile.profile_id)
+        })
+        .collect();
+    let menu = CreateSelectMenu::new(
+        format!("profile:{}", profile.user_id),
+        CreateSelectMenuKind::String { options },
+    )
+    .placeholder("Switch profile");
+    Some(CreateActionRow::SelectMenu(menu))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_embed() {
+        let profile: ZooProfileResponse =
+            serde_json::from_str(include_str!("../zoo-api/fixtures/profile.json")).unwrap();
+        let embed = serde_json::to_value(profile_embed(&profile, None).unwrap()).unwrap();
+        assert_eq!(embed["color"], 0x3fa9f5);
+        assert_eq!(embed["thumbnail"]["url"], "https://cdn.discordapp.com/avatars/100/abc.png");
+        let fields = embed["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| (field["name"].as_str().unwrap(), field["value"].as_str().unwrap()))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(fields["Relics"], "🌰 Golden Acorn");
+        assert_eq!(fields["Cosmetic"], "🌟 Polar Star");
+        assert_eq!(fields["Leader"], "🦊 Fox (level 2)");
+        assert_eq!(fields["Quest"], "Rescue a **Cat**");
+        assert!(fields["Curse"].starts_with("Curse of Sloth\nSloth: "), "{}", fields["Curse"]);
+        assert_eq!(embed["footer"]["text"], "Profile 1 of 2");
+
+        let names = BTreeMap::from([("main".to_string(), "Main Zoo".to_string())]);
+        let menu = serde_json::to_value(profile_menu(&profile, &names).unwrap()).unwrap();
+        let options = &menu["components"][0]["options"];
+        assert_eq!(options[0]["label"], "Main Zoo");
+        assert_eq!(options[0]["default"], true);
+        assert_eq!(options[1]["label"], "alt");
+        let single = ZooProfileResponse { profiles: vec!["main".to_string()], ..profile };
+        assert!(profile_menu(&single, &names).is_none());
+    }
+}
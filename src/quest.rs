@@ -0,0 +1,156 @@
+//! `/quest`: the active quest, when it finishes, who in the channel has the animal it needs, and
+//! the quest history.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use poise::serenity_prelude::{
+    FormattedTimestamp, FormattedTimestampStyle, MessageBuilder, UserId,
+};
+use zoo_api::{ZooProfileAnimal, ZooProfileResponse};
+
+use crate::{
+    index::Lookup, profile::format_quest, profile_link, push_lookup_status, Cooldown, CooldownKind,
+};
+
+/// Most channel users listed as owners of the quest animal.
+const QUEST_MAX_OWNERS: usize = 10;
+
+/// Format the active quest of the profile, when its cooldown finishes, the channel users with the
+/// quest animal (or family), and how often each quest was completed.
+pub fn format_quest_status(
+    profile: &ZooProfileResponse,
+    cooldown: Option<&Cooldown>,
+    lookup: &Lookup,
+) -> Result<String> {
+    let user_id: UserId = profile.user_id.parse()?;
+    let link = profile_link(&profile.name, user_id, Some(&profile.profile_id));
+    let mut message = MessageBuilder::new();
+    match &profile.quest {
+        Some(quest) => {
+            message
+                .push(format!("{} Active quest in ", CooldownKind::Quest.emoji()))
+                .push(link)
+                .push(": ")
+                .push_line(format_quest(quest));
+            match cooldown {
+                Some(cooldown) => message.push_line(format!(
+                    "Finishes {}",
+                    FormattedTimestamp::new(
+                        cooldown.timestamp,
+                        Some(FormattedTimestampStyle::RelativeTime)
+                    )
+                )),
+                None => message.push_line("No Quest cooldown tracked for this profile."),
+            };
+
+            // The animal if the quest has one, otherwise any animal of the family
+            let wanted = |animal: &ZooProfileAnimal| {
+                animal.amount > 0
+                    && if quest.animal.is_empty() {
+                        animal.family.eq_ignore_ascii_case(&quest.family)
+                    } else {
+                        animal.name.eq_ignore_ascii_case(&quest.animal)
+                    }
+            };
+            let mut owners = BTreeMap::<UserId, u32>::new();
+            for other in &lookup.profiles {
+                let amount = other
+                    .animals
+                    .iter()
+                    .filter(|animal| wanted(animal))
+                    .map(|animal| animal.amount)
+                    .sum::<u32>();
+                if amount > 0 {
+                    *owners.entry(other.user_id.parse()?).or_default() += amount;
+                }
+            }
+            let mut owners = owners.into_iter().collect::<Vec<_>>();
+            owners.sort_by(|(a, a_amount), (b, b_amount)| b_amount.cmp(a_amount).then(a.cmp(b)));
+            if owners.is_empty() {
+                message.push_line("Nobody in this channel has it.");
+            } else {
+                message.push("Channel users with it: ");
+                for (i, (owner, amount)) in owners.iter().take(QUEST_MAX_OWNERS).enumerate() {
+                    if i > 0 {
+                        message.push(", ");
+                    }
+                    message.user(*owner).push(format!(" ({})", amount));
+                }
+                if owners.len() > QUEST_MAX_OWNERS {
+                    message.push(format!(" and {} more", owners.len() - QUEST_MAX_OWNERS));
+                }
+                message.push_line("");
+            }
+        }
+        None => {
+            message.push("No active quest in ").push(link).push_line(".");
+        }
+    }
+
+    let mut quests = profile.quests.iter().filter(|quest| quest.completed > 0).collect::<Vec<_>>();
+    quests.sort_by(|a, b| b.completed.cmp(&a.completed).then(a.name.cmp(&b.name)));
+    if !quests.is_empty() {
+        message.push_line("").push_line("Completed quests:");
+    }
+    for quest in quests {
+        message.push("- ");
+        if !quest.emoji.is_empty() {
+            message.push(format!("{} ", quest.emoji));
+        }
+        message.push_safe(&quest.name).push_line(match quest.completed {
+            1 => ": once".to_string(),
+            completed => format!(": {} times", completed),
+        });
+    }
+    push_lookup_status(&mut message, lookup, None);
+    Ok(message.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use zoo_api::ZooProfileActiveQuest;
+
+    use super::*;
+    use crate::{mock::MockZoo, tests::cooldown, IndexConfig};
+
+    #[tokio::test]
+    async fn test_format_quest_status() {
+        let mock = MockZoo::start().await;
+        let zoo = Arc::new(mock.client());
+        let index = Arc::new(IndexConfig::default().build());
+        let user_ids = [200, 500].map(UserId::new);
+        index.refresh(&zoo, user_ids.to_vec()).await;
+        let lookup = index.lookup(&user_ids);
+        let profile: ZooProfileResponse =
+            serde_json::from_str(include_str!("../zoo-api/fixtures/profile.json")).unwrap();
+        let quest_cooldown = cooldown(100, "main", CooldownKind::Quest, 3600);
+
+        let message = format_quest_status(&profile, Some(&quest_cooldown), &lookup).unwrap();
+        let lines = message.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("🏕️ Active quest in [**Main Zoo**]"), "{}", lines[0]);
+        assert!(lines[0].ends_with(": Rescue a **Cat**"), "{}", lines[0]);
+        assert!(lines[1].starts_with("Finishes <t:"), "{}", lines[1]);
+        assert_eq!(lines[2], "Channel users with it: <@500> (2)");
+        assert_eq!(lines[4], "Completed quests:");
+        assert_eq!(lines[5], "- 🌲 Rescue Quest: 2 times");
+
+        let dog = ZooProfileActiveQuest {
+            animal: String::new(),
+            family: "Dog".to_string(),
+            ..profile.quest.clone().unwrap()
+        };
+        let profile = ZooProfileResponse { quest: Some(dog), ..profile };
+        let message = format_quest_status(&profile, None, &lookup).unwrap();
+        let lines = message.lines().collect::<Vec<_>>();
+        assert!(lines[0].ends_with(": Rescue any **Dog** family animal"), "{}", lines[0]);
+        assert_eq!(lines[1], "No Quest cooldown tracked for this profile.");
+        assert_eq!(lines[2], "Nobody in this channel has it.");
+
+        let profile = ZooProfileResponse { quest: None, quests: vec![], ..profile };
+        let message = format_quest_status(&profile, None, &lookup).unwrap();
+        assert!(message.starts_with("No active quest in [**Main Zoo**]"), "{}", message);
+    }
+}
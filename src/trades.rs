@@ -0,0 +1,201 @@
+//! `/trades`: pairs of channel users whose duplicate animals fill each other's gaps.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use poise::serenity_prelude::{MessageBuilder, UserId};
+use zoo_api::ZooProfileResponse;
+
+use crate::{catalog::Catalog, index::Lookup, push_lookup_status};
+
+/// Two users that can help each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trade {
+    pub users: [UserId; 2],
+    /// Animals the first user can give the second, and the other way around
+    pub gives: [Vec<String>; 2],
+}
+
+impl Trade {
+    pub fn gaps_filled(&self) -> usize { self.gives[0].len() + self.gives[1].len() }
+
+    /// Whether the trade involves the user.
+    pub fn has_user(&self, user_id: UserId) -> bool { self.users.contains(&user_id) }
+}
+
+/// Animals of a user, across all of their profiles.
+#[derive(Debug, Default)]
+struct Holdings {
+    owned: BTreeSet<String>,
+    /// Duplicates in a single profile, except pinned animals that the owner wants to keep
+    spare: BTreeSet<String>,
+}
+
+/// Pairs of users that both have spare animals the other one doesn't have, the ones that fill the
+/// most gaps first.
+pub fn find_trades<'a>(profiles: impl IntoIterator<Item = &'a ZooProfileResponse>) -> Vec<Trade> {
+    let mut users = BTreeMap::<UserId, Holdings>::new();
+    for profile in profiles {
+        let Ok(user_id) = profile.user_id.parse() else {
+            continue;
+        };
+        let holdings = users.entry(user_id).or_default();
+        for animal in profile.animals.iter().filter(|animal| animal.amount > 0) {
+            holdings.owned.insert(animal.name.clone());
+            if animal.amount > 1 && !animal.pinned {
+                holdings.spare.insert(animal.name.clone());
+            }
+        }
+    }
+
+    let users = users.into_iter().collect::<Vec<_>>();
+    let mut trades = vec![];
+    for (i, (a, a_holdings)) in users.iter().enumerate() {
+        for (b, b_holdings) in &users[i + 1..] {
+            let gives = |from: &Holdings, to: &Holdings| {
+                from.spare.difference(&to.owned).cloned().collect::<Vec<_>>()
+            };
+            let trade = Trade {
+                users: [*a, *b],
+                gives: [gives(a_holdings, b_holdings), gives(b_holdings, a_holdings)],
+            };
+            if trade.gives.iter().all(|gives| !gives.is_empty()) {
+                trades.push(trade);
+            }
+        }
+    }
+    trades.sort_by(|a, b| {
+        let fairness = |trade: &Trade| trade.gives[0].len().min(trade.gives[1].len());
+        b.gaps_filled()
+            .cmp(&a.gaps_filled())
+            .then_with(|| fairness(b).cmp(&fairness(a)))
+            .then_with(|| a.users.cmp(&b.users))
+    });
+    trades
+}
+
+/// Most trades listed by `/trades`.
+const TRADES_MAX: usize = 8;
+
+/// Most animals listed per side of a trade.
+const TRADES_MAX_ANIMALS: usize = 10;
+
+pub fn format_trades(catalog: &Catalog, lookup: &Lookup, user_id: Option<UserId>) -> String {
+    let trades = find_trades(lookup.profiles.iter().map(AsRef::as_ref))
+        .into_iter()
+        .filter(|trade| user_id.is_none_or(|user_id| trade.has_user(user_id)))
+        .collect::<Vec<_>>();
+    let mut message = MessageBuilder::new();
+    if trades.is_empty() {
+        message.push("No trades found");
+        if let Some(user_id) = user_id {
+            message.push(" with ").user(user_id);
+        }
+        message.push(format!(" in {} profiles.", lookup.profiles.len()));
+    } else {
+        message.push_line("Trades that fill the most missing animals:");
+    }
+    for trade in trades.iter().take(TRADES_MAX) {
+        let [a, b] = trade.users;
+        message
+            .push("- ")
+            .user(a)
+            .push(" ⇄ ")
+            .user(b)
+            .push_line(format!(": {} animals", trade.gaps_filled()));
+        for (from, gives) in trade.users.iter().zip(&trade.gives) {
+            let labels = gives
+                .iter()
+                .take(TRADES_MAX_ANIMALS)
+                .map(|name| catalog.get(name).map_or(name.clone(), |animal| animal.label()))
+                .collect::<Vec<_>>();
+            message.push("  - ").user(*from).push(" gives ").push_safe(labels.join(", "));
+            if gives.len() > TRADES_MAX_ANIMALS {
+                message.push(format!(" and {} more", gives.len() - TRADES_MAX_ANIMALS));
+            }
+            message.push_line("");
+        }
+    }
+    if trades.len() > TRADES_MAX {
+        message.push_line(format!("... and {} more", trades.len() - TRADES_MAX));
+    }
+    push_lookup_status(&mut message, lookup, None);
+    message.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use zoo_api::ZooProfileAnimal;
+
+    use super::*;
+    use crate::{mock::MockZoo, IndexConfig};
+
+    fn profile(user_id: u64, animals: &[(&str, u32, bool)]) -> ZooProfileResponse {
+        let animals = animals
+            .iter()
+            .map(|(name, amount, pinned)| ZooProfileAnimal {
+                name: name.to_string(),
+                amount: *amount,
+                pinned: *pinned,
+                ..Default::default()
+            })
+            .collect();
+        ZooProfileResponse { user_id: user_id.to_string(), animals, ..Default::default() }
+    }
+
+    #[test]
+    fn test_find_trades() {
+        let profiles = [
+            profile(1, &[("Cat", 3, false), ("Dog", 2, false), ("Owl", 0, false)]),
+            profile(2, &[("Owl", 2, false), ("Bat", 5, true)]),
+            // Second profile of user 2
+            profile(2, &[("Dog", 1, false)]),
+            profile(3, &[("Bat", 2, false), ("Wolf", 2, false)]),
+            // Nothing to give
+            profile(4, &[("Cat", 1, false)]),
+        ];
+        let trades = find_trades(&profiles);
+        assert_eq!(trades.len(), 3);
+        let users = |trade: &Trade| trade.users.map(UserId::get);
+        assert_eq!(users(&trades[0]), [1, 3]);
+        assert_eq!(trades[0].gives, [vec!["Cat", "Dog"], vec!["Bat", "Wolf"]]);
+        // User 2 already has a Dog, and the pinned Bat isn't offered
+        assert_eq!(users(&trades[1]), [1, 2]);
+        assert_eq!(trades[1].gives, [vec!["Cat"], vec!["Owl"]]);
+        assert_eq!(users(&trades[2]), [2, 3]);
+        assert_eq!(trades[2].gives, [vec!["Owl"], vec!["Wolf"]]);
+        assert!(trades[2].has_user(UserId::new(2)));
+        assert_eq!(trades[0].gaps_filled(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_format_trades() {
+        let mock = MockZoo::start().await;
+        let zoo = Arc::new(mock.client());
+        let index = Arc::new(IndexConfig::default().build());
+        let user_ids = [100, 500].map(UserId::new);
+        index.refresh(&zoo, user_ids.to_vec()).await;
+        let mut catalog = Catalog::default();
+        catalog.update(index.all_profiles().iter().map(AsRef::as_ref));
+        // User 500 has no duplicates, the pinned Cat of user 100 isn't offered
+        let message = format_trades(&catalog, &index.lookup(&user_ids), None);
+        assert!(message.starts_with("No trades found in 3 profiles."), "{}", message);
+
+        let mut profile = zoo.fetch_profile(500, None).await.unwrap().as_ref().clone();
+        profile.animals.push(ZooProfileAnimal {
+            name: "Bat".to_string(),
+            amount: 2,
+            ..Default::default()
+        });
+        let mut lookup = index.lookup(&user_ids);
+        lookup.profiles.push(Arc::new(profile));
+        let message = format_trades(&catalog, &lookup, Some(UserId::new(500)));
+        let lines = message.lines().collect::<Vec<_>>();
+        assert_eq!(lines[1], "- <@100> ⇄ <@500>: 2 animals");
+        assert_eq!(lines[2], "  - <@100> gives 🐶 Dog");
+        assert_eq!(lines[3], "  - <@500> gives Bat");
+        let message = format_trades(&catalog, &lookup, Some(UserId::new(200)));
+        assert!(message.starts_with("No trades found with <@200> in 4 profiles."), "{}", message);
+    }
+}
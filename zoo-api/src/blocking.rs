@@ -0,0 +1,89 @@
+//! Blocking client, for tools without an async runtime.
+
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    profile_api_url, request_interval,
+    response::{parse_response, retry_after, retry_delay},
+    ZooClientBuilder, ZooError, ZooProfileResponse,
+};
+
+/// Blocking Zoo API client. Unlike the async client it doesn't cache profiles.
+///
+/// Created with [`ZooClientBuilder::build_blocking`].
+pub struct ZooClient {
+    client: reqwest::blocking::Client,
+    base_url: String,
+    timeout: Duration,
+    max_retries: u32,
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl ZooClient {
+    pub(crate) fn new(builder: ZooClientBuilder) -> Self {
+        ZooClient {
+            client: reqwest::blocking::Client::new(),
+            base_url: builder.base_url,
+            timeout: builder.timeout,
+            max_retries: builder.max_retries,
+            interval: request_interval(builder.requests_per_second),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Fetch a profile, or the user's current profile if `profile` is `None`.
+    pub fn fetch_profile(
+        &self,
+        user_id: u64,
+        profile: Option<&str>,
+    ) -> Result<ZooProfileResponse, ZooError> {
+        let api_url = profile_api_url(&self.base_url, user_id, profile);
+        let mut attempt = 0;
+        loop {
+            self.acquire();
+            let error = match self.send(&api_url) {
+                Ok(profile) => return Ok(profile),
+                Err(e) => e,
+            };
+            if let ZooError::RateLimited { retry_after: Some(retry_after) } = error {
+                let mut next = self.next.lock().unwrap();
+                *next = (*next).max(Instant::now() + retry_after);
+            }
+            attempt += 1;
+            let Some(delay) = retry_delay(&error, attempt, self.max_retries) else {
+                return Err(error);
+            };
+            tracing::warn!(
+                "Retrying {} in {:.1}s (attempt {}/{}): {:?}",
+                api_url,
+                delay.as_secs_f64(),
+                attempt,
+                self.max_retries,
+                error
+            );
+            thread::sleep(delay);
+        }
+    }
+
+    /// Wait for the next free request slot.
+    fn acquire(&self) {
+        let mut next = self.next.lock().unwrap();
+        thread::sleep(next.saturating_duration_since(Instant::now()));
+        *next = Instant::now() + self.interval;
+    }
+
+    fn send(&self, api_url: &str) -> Result<ZooProfileResponse, ZooError> {
+        let unavailable = |e: reqwest::Error| ZooError::Unavailable(e.to_string());
+        let response =
+            self.client.get(api_url).timeout(self.timeout).send().map_err(unavailable)?;
+        let status = response.status();
+        let retry_after = retry_after(response.headers());
+        let text = response.text().map_err(unavailable)?;
+        parse_response(status, retry_after, &text)
+    }
+}
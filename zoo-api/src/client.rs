@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tokio::sync::OnceCell;
+
+use crate::{
+    profile_api_url, request_interval,
+    response::{parse_response, retry_after, retry_delay},
+    ZooClientBuilder, ZooError, ZooProfileResponse,
+};
+
+/// Spaces out requests to the API.
+struct RateLimiter {
+    interval: Duration,
+    next: tokio::sync::Mutex<tokio::time::Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: f64) -> Self {
+        RateLimiter {
+            interval: request_interval(requests_per_second),
+            next: tokio::sync::Mutex::new(tokio::time::Instant::now()),
+        }
+    }
+
+    /// Wait for the next free slot.
+    async fn acquire(&self) {
+        let mut next = self.next.lock().await;
+        tokio::time::sleep_until(*next).await;
+        *next = tokio::time::Instant::now() + self.interval;
+    }
+
+    /// Don't send any requests for a while, e.g. when rate limited.
+    async fn pause(&self, duration: Duration) {
+        let mut next = self.next.lock().await;
+        *next = (*next).max(tokio::time::Instant::now() + duration);
+    }
+}
+
+type CacheKey = (u64, Option<String>);
+/// Failures are kept until the request is done, so everyone waiting for it gets the same error.
+type CacheCell = Arc<OnceCell<Result<(Instant, Arc<ZooProfileResponse>), ZooError>>>;
+
+/// Zoo API client that caches profiles for a while, and shares a single request between
+/// concurrent callers asking for the same profile.
+pub struct ZooClient {
+    client: reqwest::Client,
+    base_url: String,
+    ttl: Duration,
+    timeout: Duration,
+    max_retries: u32,
+    limiter: RateLimiter,
+    cache: Mutex<HashMap<CacheKey, CacheCell>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Requests that waited for an identical request already in flight
+    pub coalesced: u64,
+    /// Cached profiles, including expired ones that weren't dropped yet
+    pub entries: usize,
+}
+
+impl ZooClient {
+    pub fn builder() -> ZooClientBuilder { ZooClientBuilder::default() }
+
+    pub(crate) fn new(builder: ZooClientBuilder) -> Self {
+        ZooClient {
+            client: builder.client.unwrap_or_default(),
+            base_url: builder.base_url,
+            ttl: builder.ttl,
+            timeout: builder.timeout,
+            max_retries: builder.max_retries,
+            limiter: RateLimiter::new(builder.requests_per_second),
+            cache: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+
+    /// Fetch a profile, or the user's current profile if `profile` is `None`.
+    pub async fn fetch_profile(
+        &self,
+        user_id: u64,
+        profile: Option<&str>,
+    ) -> Result<Arc<ZooProfileResponse>, ZooError> {
+        let key = (user_id, profile.map(str::to_string));
+        let cell = {
+            let mut cache = self.cache.lock().unwrap();
+            // Empty cells are requests in flight, failed ones are removed
+            match cache.get(&key).map(|cell| (cell, cell.get())) {
+                Some((_, Some(Ok((fetched, response))))) if fetched.elapsed() < self.ttl => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(response.clone());
+                }
+                Some((cell, None)) => {
+                    self.coalesced.fetch_add(1, Ordering::Relaxed);
+                    cell.clone()
+                }
+                _ => {
+                    self.misses.fetch_add(1, Ordering::Relaxed);
+                    self.prune(&mut cache);
+                    let cell = CacheCell::default();
+                    cache.insert(key.clone(), cell.clone());
+                    cell
+                }
+            }
+        };
+        let result = cell
+            .get_or_init(|| async {
+                let response = self.request_profile(user_id, profile).await?;
+                Ok((Instant::now(), Arc::new(response)))
+            })
+            .await;
+        let (_, response) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                // Don't cache the error, the next request is a miss again
+                let mut cache = self.cache.lock().unwrap();
+                if cache.get(&key).is_some_and(|cached| Arc::ptr_eq(cached, &cell)) {
+                    cache.remove(&key);
+                }
+                return Err(e.clone());
+            }
+        };
+        if profile.is_none() {
+            // The current profile can also be asked for by ID
+            let key = (user_id, Some(response.profile_id.clone()));
+            let mut cache = self.cache.lock().unwrap();
+            let cell = cache.entry(key).or_default();
+            if cell.get().is_some_and(|cached| !self.is_fresh(cached)) {
+                *cell = CacheCell::default();
+            }
+            let _ = cell.set(Ok((Instant::now(), response.clone())));
+        }
+        Ok(response.clone())
+    }
+
+    async fn request_profile(
+        &self,
+        user_id: u64,
+        profile: Option<&str>,
+    ) -> Result<ZooProfileResponse, ZooError> {
+        let api_url = profile_api_url(&self.base_url, user_id, profile);
+        let mut attempt = 0;
+        loop {
+            self.limiter.acquire().await;
+            let error = match self.send(&api_url).await {
+                Ok(profile) => return Ok(profile),
+                Err(e) => e,
+            };
+            if let ZooError::RateLimited { retry_after: Some(retry_after) } = error {
+                // Applies to every request, not just this one
+                self.limiter.pause(retry_after).await;
+            }
+            attempt += 1;
+            let Some(delay) = retry_delay(&error, attempt, self.max_retries) else {
+                return Err(error);
+            };
+            tracing::warn!(
+                "Retrying {} in {:.1}s (attempt {}/{}): {:?}",
+                api_url,
+                delay.as_secs_f64(),
+                attempt,
+                self.max_retries,
+                error
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn send(&self, api_url: &str) -> Result<ZooProfileResponse, ZooError> {
+        let unavailable = |e: reqwest::Error| ZooError::Unavailable(e.to_string());
+        let response =
+            self.client.get(api_url).timeout(self.timeout).send().await.map_err(unavailable)?;
+        let status = response.status();
+        let retry_after = retry_after(response.headers());
+        let text = response.text().await.map_err(unavailable)?;
+        parse_response(status, retry_after, &text)
+    }
+
+    /// Forget the cached current profile of a user, e.g. after switching profiles.
+    pub fn invalidate_current(&self, user_id: u64) {
+        self.cache.lock().unwrap().remove(&(user_id, None));
+    }
+
+    /// Forget all cached profiles of a user, e.g. after their animals changed.
+    pub fn invalidate_user(&self, user_id: u64) {
+        self.cache.lock().unwrap().retain(|(id, _), _| *id != user_id);
+    }
+
+    /// Drop expired profiles, so the cache doesn't keep every user ever asked for. Requests in
+    /// flight are kept.
+    fn prune(&self, cache: &mut HashMap<CacheKey, CacheCell>) {
+        cache.retain(|_, cell| cell.get().is_none_or(|cached| self.is_fresh(cached)));
+    }
+
+    fn is_fresh(&self, cached: &Result<(Instant, Arc<ZooProfileResponse>), ZooError>) -> bool {
+        cached.as_ref().is_ok_and(|(fetched, _)| fetched.elapsed() < self.ttl)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let cache = self.cache.lock().unwrap();
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            entries: cache.values().filter(|cell| cell.get().is_some_and(Result::is_ok)).count(),
+        }
+    }
+}
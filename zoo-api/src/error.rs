@@ -0,0 +1,33 @@
+use std::{sync::Arc, time::Duration};
+
+use reqwest::StatusCode;
+
+/// Errors are shown to users, so the messages are kept short. Response bodies are only included
+/// in the `Debug` output for logs.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ZooError {
+    #[error("User doesn't have a zoo")]
+    NoZoo,
+    #[error("Profile is private")]
+    Private,
+    #[error("Rate limited by the Zoo API")]
+    RateLimited { retry_after: Option<Duration> },
+    /// With the cause, e.g. the status and response body
+    #[error("Zoo API is unavailable")]
+    Unavailable(String),
+    #[error("Unexpected response from the Zoo API")]
+    Schema {
+        #[source]
+        error: Arc<serde_json::Error>,
+        body: String,
+    },
+    #[error("Zoo API request failed with {status}")]
+    Http { status: StatusCode, body: String },
+}
+
+impl ZooError {
+    /// Whether the same request may succeed later.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ZooError::RateLimited { .. } | ZooError::Unavailable(_))
+    }
+}
@@ -0,0 +1,211 @@
+//! Parsing that survives changes to the API.
+//!
+//! A field with an unexpected type is dropped and parsing is retried, so the field gets its
+//! default instead of failing the whole profile. Unexpected and unknown fields are logged once
+//! per field, so API changes are noticed early.
+
+use std::{collections::BTreeSet, sync::Mutex};
+
+use serde_json::Value;
+use serde_path_to_error::{Path, Segment};
+
+use crate::{model::ID_FIELDS, ZooProfileResponse};
+
+/// Fields that have already been warned about.
+static WARNED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Most fields dropped from a single response before giving up.
+const MAX_REPAIRS: usize = 32;
+
+/// Whether this is the first warning about a field.
+fn first_warning(field: &str) -> bool { WARNED.lock().unwrap().insert(field.to_string()) }
+
+pub(crate) fn parse_profile(text: &str) -> Result<ZooProfileResponse, serde_json::Error> {
+    let original: Value = serde_json::from_str(text)?;
+    let mut value = original.clone();
+    let mut repairs = 0;
+    let profile = loop {
+        let error = match serde_path_to_error::deserialize::<_, ZooProfileResponse>(&value) {
+            Ok(profile) => break profile,
+            Err(e) => e,
+        };
+        let path = error.path().clone();
+        let error = error.into_inner();
+        let is_id = matches!(
+            path.iter().next(),
+            Some(Segment::Map { key }) if ID_FIELDS.contains(&key.as_str())
+        );
+        if is_id || repairs == MAX_REPAIRS || !remove(&mut value, &path) {
+            return Err(error);
+        }
+        repairs += 1;
+        let field = field_name(&path);
+        if first_warning(&field) {
+            tracing::warn!(field, %error, "Zoo API field has an unexpected type, using the default");
+        }
+    };
+    profile.validate()?;
+
+    let mut unknown = vec![];
+    unknown_fields(&original, &serde_json::to_value(&profile)?, "", &mut unknown);
+    for field in unknown {
+        if first_warning(&field) {
+            tracing::warn!(field, "Unknown field in Zoo API response");
+        }
+    }
+    Ok(profile)
+}
+
+/// Field path without array indices, e.g. `animals[].amount`.
+fn field_name(path: &Path) -> String {
+    let mut name = String::new();
+    for segment in path {
+        match segment {
+            Segment::Seq { .. } => name.push_str("[]"),
+            Segment::Map { key } | Segment::Enum { variant: key } => {
+                if !name.is_empty() {
+                    name.push('.');
+                }
+                name.push_str(key);
+            }
+            Segment::Unknown => name.push_str(".?"),
+        }
+    }
+    name
+}
+
+/// Remove the value at `path`. Returns whether anything was removed.
+///
+/// An array element with a mistyped name is removed as a whole, since a default blank name would
+/// turn it into a nameless animal, relic or goal.
+fn remove(value: &mut Value, path: &Path) -> bool {
+    let mut segments = path.iter().collect::<Vec<_>>();
+    if let [.., Segment::Seq { .. }, Segment::Map { key }] = segments.as_slice() {
+        if key == "name" {
+            segments.pop();
+        }
+    }
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+    let mut parent = value;
+    for segment in parents {
+        let child = match (segment, parent) {
+            (Segment::Map { key }, Value::Object(object)) => object.get_mut(key),
+            (Segment::Seq { index }, Value::Array(array)) => array.get_mut(*index),
+            _ => None,
+        };
+        let Some(child) = child else {
+            return false;
+        };
+        parent = child;
+    }
+    match (last, parent) {
+        (Segment::Map { key }, Value::Object(object)) => object.remove(key).is_some(),
+        (Segment::Seq { index }, Value::Array(array)) if *index < array.len() => {
+            array.remove(*index);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Collect fields of `original` that don't survive parsing.
+fn unknown_fields(original: &Value, parsed: &Value, prefix: &str, unknown: &mut Vec<String>) {
+    match (original, parsed) {
+        (Value::Object(original), Value::Object(parsed)) => {
+            for (key, value) in original {
+                let field = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                match parsed.get(key) {
+                    Some(parsed) => unknown_fields(value, parsed, &field, unknown),
+                    // Skipped when serializing
+                    None if value.is_null() => {}
+                    None => unknown.push(field),
+                }
+            }
+        }
+        (Value::Array(original), Value::Array(parsed)) => {
+            let field = format!("{prefix}[]");
+            for (value, parsed) in original.iter().zip(parsed) {
+                unknown_fields(value, parsed, &field, unknown);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: &str = include_str!("../fixtures/profile.json");
+
+    fn drifted(change: impl FnOnce(&mut Value)) -> String {
+        let mut value: Value = serde_json::from_str(PROFILE).unwrap();
+        change(&mut value);
+        value.to_string()
+    }
+
+    #[test]
+    fn test_mistyped_fields() {
+        let text = drifted(|value| {
+            value["score"] = "a lot".into();
+            value["animals"][1]["amount"] = Value::Null;
+            value["terminal"]["fishy"] = 5.into();
+            value["settings"] = Value::Array(vec![]);
+            value["animals"][2]["name"] = 5.into();
+            value["relics"][0] = Value::Null;
+            value["profiles"][0] = 5.into();
+        });
+        assert!(serde_json::from_str::<ZooProfileResponse>(&text).is_err());
+        let profile = parse_profile(&text).unwrap();
+        assert_eq!(profile.score, 0);
+        assert_eq!(profile.animals[0].amount, 5);
+        assert_eq!(profile.animals[1].amount, 0);
+        assert_eq!(profile.animals[1].name, "Lion");
+        assert!(profile.terminal.fishy.is_none());
+        assert!(profile.terminal.garden.is_some());
+        assert!(!profile.settings.show_animal_totals);
+        assert_eq!(profile.animals.len(), 2);
+        assert!(profile.relics.is_empty());
+        assert_eq!(profile.profiles, ["alt"]);
+    }
+
+    #[test]
+    fn test_ids_are_strict() {
+        let text = drifted(|value| value["userID"] = 100.into());
+        assert!(parse_profile(&text).is_err());
+        let text = drifted(|value| {
+            value.as_object_mut().unwrap().remove("profileID");
+        });
+        assert!(parse_profile(&text).is_err());
+        assert!(parse_profile(r#"{"error":"This zoo is private!"}"#).is_err());
+        assert!(parse_profile("<html>").is_err());
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let text = drifted(|value| {
+            value["newField"] = 1.into();
+            value["animals"][0]["shiny"] = true.into();
+            value["animals"][2]["shiny"] = false.into();
+            value["quest"]["reward"] = Value::Null;
+        });
+        let original: Value = serde_json::from_str(&text).unwrap();
+        let profile = parse_profile(&text).unwrap();
+        let mut unknown = vec![];
+        unknown_fields(&original, &serde_json::to_value(&profile).unwrap(), "", &mut unknown);
+        unknown.dedup();
+        assert_eq!(unknown, ["animals[].shiny", "newField"]);
+    }
+
+    #[test]
+    fn test_field_name() {
+        let text = drifted(|value| value["animals"][1]["amount"] = "x".into());
+        let value: Value = serde_json::from_str(&text).unwrap();
+        let error = serde_path_to_error::deserialize::<_, ZooProfileResponse>(&value).unwrap_err();
+        assert_eq!(field_name(error.path()), "animals[].amount");
+        assert!(first_warning("test.field"));
+        assert!(!first_warning("test.field"));
+    }
+}
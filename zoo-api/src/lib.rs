@@ -0,0 +1,161 @@
+//! Typed client for the [Zoo](https://gdcolon.com/zoo) profile API.
+//!
+//! The async client (feature `async`, on by default) caches profiles and shares concurrent
+//! requests for the same profile. The blocking client (feature `blocking`) sends every request.
+//! Both rate limit, time out and retry requests the same way.
+//!
+//! ```no_run
+//! # #[cfg(feature = "async")]
+//! # async fn example() -> Result<(), zoo_api::ZooError> {
+//! let client = zoo_api::ZooClient::builder().rate_limit(1.0).build();
+//! let profile = client.fetch_profile(123456789012345678, None).await?;
+//! println!("{} has {} animals", profile.name, profile.animals.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The same with the blocking client:
+//!
+//! ```no_run
+//! # #[cfg(feature = "blocking")]
+//! # fn example() -> Result<(), zoo_api::ZooError> {
+//! let client = zoo_api::ZooClientBuilder::default().rate_limit(1.0).build_blocking();
+//! let profile = client.fetch_profile(123456789012345678, None)?;
+//! println!("{} has {} animals", profile.name, profile.animals.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Without either feature only the response types are available.
+
+use std::time::Duration;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "async")]
+mod client;
+mod error;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod lenient;
+mod model;
+#[cfg(any(feature = "async", feature = "blocking"))]
+mod response;
+
+#[cfg(feature = "async")]
+pub use client::{CacheStats, ZooClient};
+pub use error::ZooError;
+pub use model::*;
+
+pub const DEFAULT_BASE_URL: &str = "https://gdcolon.com/zoo";
+
+fn profile_path(user_id: u64, profile: Option<&str>) -> String {
+    if let Some(profile) = profile {
+        format!("{}_{}", user_id, profile)
+    } else {
+        user_id.to_string()
+    }
+}
+
+/// Link to a profile on the website. Always the public site, even if the API base URL is changed.
+pub fn profile_url(user_id: u64, profile: Option<&str>) -> String {
+    format!("{}/{}", DEFAULT_BASE_URL, profile_path(user_id, profile))
+}
+
+pub fn profile_api_url(base_url: &str, user_id: u64, profile: Option<&str>) -> String {
+    format!("{}/api/profile/{}", base_url.trim_end_matches('/'), profile_path(user_id, profile))
+}
+
+/// Settings for [`ZooClient`] and [`blocking::ZooClient`].
+pub struct ZooClientBuilder {
+    #[cfg(feature = "async")]
+    client: Option<reqwest::Client>,
+    base_url: String,
+    ttl: Duration,
+    timeout: Duration,
+    max_retries: u32,
+    requests_per_second: f64,
+}
+
+impl Default for ZooClientBuilder {
+    fn default() -> Self {
+        ZooClientBuilder {
+            #[cfg(feature = "async")]
+            client: None,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            ttl: Duration::from_secs(120),
+            timeout: Duration::from_secs(10),
+            max_retries: 3,
+            requests_per_second: 2.0,
+        }
+    }
+}
+
+impl ZooClientBuilder {
+    /// HTTP client to send the requests with
+    #[cfg(feature = "async")]
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Base URL of the API, without the `/api` part
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// How long fetched profiles are reused. Only used by the async client.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Timeout of a single request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retries on rate limits, server errors and network errors
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Maximum requests per second, across all callers. Zero means unlimited.
+    pub fn rate_limit(mut self, requests_per_second: f64) -> Self {
+        self.requests_per_second = requests_per_second;
+        self
+    }
+
+    #[cfg(feature = "async")]
+    pub fn build(self) -> ZooClient { ZooClient::new(self) }
+
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> blocking::ZooClient { blocking::ZooClient::new(self) }
+}
+
+/// Time between requests for a rate limit, zero if unlimited.
+#[cfg(any(feature = "async", feature = "blocking"))]
+fn request_interval(requests_per_second: f64) -> Duration {
+    if requests_per_second > 0.0 {
+        Duration::from_secs_f64(1.0 / requests_per_second)
+    } else {
+        Duration::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urls() {
+        assert_eq!(profile_url(1, None), "https://gdcolon.com/zoo/1");
+        assert_eq!(profile_url(1, Some("abc")), "https://gdcolon.com/zoo/1_abc");
+        assert_eq!(
+            profile_api_url("http://localhost:8080/", 1, Some("abc")),
+            "http://localhost:8080/api/profile/1_abc"
+        );
+    }
+}
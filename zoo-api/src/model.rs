@@ -0,0 +1,379 @@
+//! Profile response of the Zoo API.
+//!
+//! Every field defaults if it's missing, so new or removed fields don't break parsing. Only the
+//! IDs are required, see [`ZooProfileResponse::validate`].
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileUser {
+    pub avatar: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileUniqueAnimals {
+    pub common: u32,
+    pub rare: u32,
+    pub total: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileTotalAnimals {
+    pub common: u32,
+    pub rare: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileAnimal {
+    pub name: String,
+    pub amount: u32,
+    pub emoji: String,
+    #[serde(rename = "emojiName")]
+    pub emoji_name: String,
+    pub family: String,
+    pub rare: bool,
+    pub pinned: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileRelic {
+    pub name: String,
+    pub emoji: String,
+    pub description: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileCosmetic {
+    pub name: String,
+    pub emoji: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileLeader {
+    pub name: String,
+    pub emoji: String,
+    pub triggered: u32,
+    pub xp: u32,
+    pub level: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileQuest {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub emoji: String,
+    pub days: u32,
+    pub completed: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileActiveQuest {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub animal: String,
+    pub family: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileCurse {
+    pub name: String,
+    pub names: ZooProfileCurseNames,
+    pub weak: bool,
+    pub effects: ZooProfileCurseEffects,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileCurseNames {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub cure: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileCurseEffects {
+    #[serde(rename = "type")]
+    pub kind: ZooProfileCurseEffect,
+    pub cure: ZooProfileCurseEffect,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileCurseEffect {
+    pub name: String,
+    pub description: String,
+    // TODO nullable?
+    // pub weak: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileTerminalFishy {
+    #[serde(rename = "commonFish")]
+    pub common_fish: u32,
+    #[serde(rename = "uncommonFish")]
+    pub uncommon_fish: u32,
+    #[serde(rename = "rareFish")]
+    pub rare_fish: u32,
+    pub trash: u32,
+    pub pebbles: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileTerminalGarden {
+    pub unlocked: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileTerminalCards {
+    pub total: u32,
+    pub common: u32,
+    pub rare: u32,
+    #[serde(rename = "ultraRare")]
+    pub ultra_rare: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileTerminalFusionFusions {
+    #[serde(rename = "commonCommon")]
+    pub common_common: u32,
+    #[serde(rename = "commonRare")]
+    pub common_rare: u32,
+    #[serde(rename = "rareRare")]
+    pub rare_rare: u32,
+    pub total: u32,
+    pub score: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileTerminalFusionNfbs {
+    pub common: u32,
+    pub rare: u32,
+    pub total: u32,
+    pub score: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileTerminalFusion {
+    #[serde(rename = "tokensPerRescue")]
+    pub tokens_per_rescue: u32,
+    #[serde(rename = "tokensFromFusions")]
+    pub tokens_from_fusions: u32,
+    #[serde(rename = "nfbMultiplier")]
+    pub nfb_multiplier: f32,
+    pub fusions: ZooProfileTerminalFusionFusions,
+    pub nfbs: ZooProfileTerminalFusionNfbs,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileTerminal {
+    pub unlocked: bool,
+    pub admin: bool,
+    #[serde(rename = "commandsFound")]
+    pub commands_found: u32,
+    #[serde(rename = "mechanicPoints")]
+    pub mechanic_points: u32,
+    // Minigames are missing until they're unlocked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fishy: Option<ZooProfileTerminalFishy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub garden: Option<ZooProfileTerminalGarden>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cards: Option<ZooProfileTerminalCards>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fusion: Option<ZooProfileTerminalFusion>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileStat {
+    pub name: String,
+    pub value: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileGoal {
+    pub name: String,
+    pub emoji: String,
+    pub tier: String,
+    #[serde(rename = "tierNumber")]
+    pub tier_number: u32,
+    pub target: u32,
+    pub desc: String,
+    pub count: u32,
+    pub complete: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileSettings {
+    #[serde(rename = "altTimestamp")]
+    pub alt_timestamp: bool,
+    #[serde(rename = "fastConfirmations")]
+    pub fast_confirmations: bool,
+    #[serde(rename = "showAnimalTotals")]
+    pub show_animal_totals: bool,
+    #[serde(rename = "disableNotifications")]
+    pub disable_notifications: bool,
+    #[serde(rename = "disableAutoRescues")]
+    pub disable_auto_rescues: bool,
+    #[serde(rename = "disableQuestNotifications")]
+    pub disable_quest_notifications: bool,
+    #[serde(rename = "disableCustomColor")]
+    pub disable_custom_color: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ZooProfileResponse {
+    pub id: String,
+    #[serde(rename = "userID")]
+    pub user_id: String,
+    #[serde(rename = "profileID")]
+    pub profile_id: String,
+    #[serde(rename = "selectedProfile")]
+    pub selected_profile: String,
+    pub profiles: Vec<String>,
+    pub user: ZooProfileUser,
+    pub name: String, // zoo name
+    pub nickname: String,
+    pub color: Option<String>, // hex color without #
+    pub owner: bool,
+    pub private: bool,
+    #[serde(rename = "profileTheme")]
+    pub profile_theme: String,
+    pub score: u32,
+    pub completion: f32,
+    #[serde(rename = "uniqueAnimals")]
+    pub unique_animals: ZooProfileUniqueAnimals,
+    #[serde(rename = "totalAnimals")]
+    pub total_animals: ZooProfileTotalAnimals,
+    #[serde(rename = "totalItems")]
+    pub total_items: u32,
+    #[serde(rename = "totalCosmetics")]
+    pub total_cosmetics: u32,
+    #[serde(rename = "totalTrophies")]
+    pub total_trophies: u32,
+    #[serde(rename = "totalLeaderXP")]
+    pub total_leader_xp: u32,
+    #[serde(rename = "unspentLeaderXP")]
+    pub unspent_leader_xp: u32,
+    #[serde(rename = "equippedRelics")]
+    pub equipped_relics: Vec<String>,
+    #[serde(rename = "equippedCosmetic")]
+    pub equipped_cosmetic: Option<String>,
+    #[serde(rename = "equippedLeader")]
+    pub equipped_leader: Option<String>,
+    #[serde(rename = "cosmeticIcon")]
+    pub cosmetic_icon: Option<String>,
+    pub notifications: u32,
+    #[serde(rename = "autoRescues")]
+    pub auto_rescues: u32,
+    pub animals: Vec<ZooProfileAnimal>,
+    pub relics: Vec<ZooProfileRelic>,
+    pub cosmetics: Vec<ZooProfileCosmetic>,
+    pub leaders: Vec<ZooProfileLeader>,
+    pub quests: Vec<ZooProfileQuest>,
+    pub quest: Option<ZooProfileActiveQuest>,
+    pub curse: Option<ZooProfileCurse>,
+    pub terminal: ZooProfileTerminal,
+    pub stats: Vec<ZooProfileStat>,
+    pub goals: Vec<ZooProfileGoal>,
+    #[serde(rename = "goalTiers")]
+    pub goal_tiers: u32,
+    #[serde(rename = "goalsComplete")]
+    pub goals_complete: u32,
+    #[serde(rename = "extraData")]
+    pub extra_data: Vec<Vec<String>>,
+    pub settings: ZooProfileSettings,
+}
+
+/// Fields that identify a profile, and are never defaulted.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(crate) const ID_FIELDS: [&str; 3] = ["id", "userID", "profileID"];
+
+impl ZooProfileResponse {
+    /// Check the fields that can't be defaulted.
+    pub fn validate(&self) -> Result<(), serde_json::Error> {
+        use serde::de::Error;
+
+        if self.id.is_empty() {
+            return Err(serde_json::Error::missing_field("id"));
+        }
+        if self.user_id.parse::<u64>().is_err() {
+            return Err(serde_json::Error::custom(format!("invalid userID {:?}", self.user_id)));
+        }
+        if self.profile_id.is_empty() {
+            return Err(serde_json::Error::missing_field("profileID"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: &str = include_str!("../fixtures/profile.json");
+    const PROFILE_NEW: &str = include_str!("../fixtures/profile_new.json");
+
+    /// Deserialize, and check that serializing and deserializing again gives the same profile.
+    fn round_trip(json: &str) -> ZooProfileResponse {
+        let profile: ZooProfileResponse = serde_json::from_str(json).unwrap();
+        let serialized = serde_json::to_string(&profile).unwrap();
+        assert_eq!(serde_json::from_str::<ZooProfileResponse>(&serialized).unwrap(), profile);
+        profile
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let profile = round_trip(PROFILE);
+        // Every field is in the fixture, so the field names must match the API
+        assert_eq!(
+            serde_json::to_value(&profile).unwrap(),
+            serde_json::from_str::<serde_json::Value>(PROFILE).unwrap()
+        );
+        assert_eq!(profile.profile_id, "main");
+        assert_eq!(profile.animals.len(), 3);
+        assert_eq!(profile.curse.as_ref().unwrap().effects.cure.name, "Speed");
+        assert_eq!(profile.stats[0], ZooProfileStat { name: "Animals rescued".into(), value: 48 });
+        assert_eq!(profile.extra_data[1], ["Favorite animal", "Cat"]);
+    }
+
+    #[test]
+    fn test_terminal_minigames() {
+        let terminal = round_trip(PROFILE).terminal;
+        assert_eq!(terminal.fishy.unwrap().pebbles, 20);
+        assert!(terminal.garden.unwrap().unlocked);
+        assert_eq!(terminal.cards.unwrap().ultra_rare, 1);
+        let fusion = terminal.fusion.unwrap();
+        assert_eq!(fusion.nfb_multiplier, 1.5);
+        assert_eq!(fusion.fusions.total, 4);
+        assert_eq!(fusion.nfbs.score, 5);
+
+        let terminal = round_trip(PROFILE_NEW).terminal;
+        assert!(!terminal.unlocked);
+        assert_eq!(terminal.commands_found, 0);
+        assert!(terminal.fishy.is_none());
+        assert!(terminal.garden.is_none());
+        assert!(terminal.cards.is_none());
+        assert!(terminal.fusion.is_none());
+    }
+}
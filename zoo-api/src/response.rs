@@ -0,0 +1,157 @@
+//! Turning API responses into profiles or errors, and deciding when to retry. Shared by both
+//! clients.
+
+use std::{sync::Arc, time::Duration};
+
+use reqwest::{header::HeaderMap, StatusCode};
+
+use crate::{ZooError, ZooProfileResponse};
+
+impl ZooError {
+    pub(crate) fn from_response(
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        text: &str,
+    ) -> Self {
+        #[derive(serde::Deserialize)]
+        struct ErrorBody {
+            error: String,
+        }
+
+        let message = serde_json::from_str::<ErrorBody>(text)
+            .map(|body| body.error.to_lowercase())
+            .unwrap_or_default();
+        if status == StatusCode::FORBIDDEN || message.contains("private") {
+            ZooError::Private
+        } else if status == StatusCode::NOT_FOUND
+            || ["no zoo", "not found", "invalid user"].iter().any(|s| message.contains(s))
+        {
+            ZooError::NoZoo
+        } else if status == StatusCode::TOO_MANY_REQUESTS {
+            ZooError::RateLimited { retry_after }
+        } else if status.is_server_error() {
+            ZooError::Unavailable(format!("{}: {}", status, truncate_body(text)))
+        } else {
+            ZooError::Http { status, body: truncate_body(text) }
+        }
+    }
+}
+
+/// Longest `Retry-After` that's waited for before giving up.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Longest backoff between retries, before jitter.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Longest part of a response body included in errors.
+const MAX_ERROR_BODY: usize = 300;
+
+fn truncate_body(text: &str) -> String {
+    if text.len() <= MAX_ERROR_BODY {
+        return text.to_string();
+    }
+    let mut end = MAX_ERROR_BODY;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}… ({} bytes total)", &text[..end], text.len())
+}
+
+/// Parse a `Retry-After` header, either in seconds or as an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(secs) = value.trim().parse::<f64>() {
+        return Duration::try_from_secs_f64(secs).ok();
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok()
+}
+
+pub(crate) fn parse_response(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    text: &str,
+) -> Result<ZooProfileResponse, ZooError> {
+    if !status.is_success() {
+        return Err(ZooError::from_response(status, retry_after, text));
+    }
+    crate::lenient::parse_profile(text).map_err(|error| {
+        // Some errors are sent with a success status
+        match ZooError::from_response(status, retry_after, text) {
+            e @ (ZooError::NoZoo | ZooError::Private) => e,
+            _ => ZooError::Schema { error: Arc::new(error), body: truncate_body(text) },
+        }
+    })
+}
+
+/// How long to wait before retry number `attempt` (starting at 1), or `None` to give up.
+pub(crate) fn retry_delay(error: &ZooError, attempt: u32, max_retries: u32) -> Option<Duration> {
+    if !error.is_transient() || attempt > max_retries {
+        return None;
+    }
+    match error {
+        ZooError::RateLimited { retry_after: Some(retry_after) } => {
+            (*retry_after <= MAX_RETRY_AFTER).then_some(*retry_after)
+        }
+        _ => {
+            // Exponential backoff with up to 50% jitter
+            let backoff = Duration::from_millis(500)
+                .saturating_mul(2u32.saturating_pow(attempt - 1))
+                .min(MAX_BACKOFF);
+            Some(backoff.mul_f64(1.0 + rand::random::<f64>() * 0.5))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_from_response() {
+        let error = |status: u16, text: &str| {
+            ZooError::from_response(StatusCode::from_u16(status).unwrap(), None, text)
+        };
+        assert!(matches!(error(404, ""), ZooError::NoZoo));
+        assert!(matches!(error(400, r#"{"error":"Invalid user ID"}"#), ZooError::NoZoo));
+        assert!(matches!(error(200, r#"{"error":"This user has no zoo!"}"#), ZooError::NoZoo));
+        assert!(matches!(error(403, ""), ZooError::Private));
+        assert!(matches!(error(200, r#"{"error":"This profile is private"}"#), ZooError::Private));
+        assert!(matches!(error(429, ""), ZooError::RateLimited { retry_after: None }));
+        assert!(matches!(error(502, "<html>Bad Gateway</html>"), ZooError::Unavailable(_)));
+        assert!(matches!(error(418, "I'm a teapot"), ZooError::Http { .. }));
+        // The body is only logged
+        let unavailable = error(502, "<html>Bad Gateway</html>");
+        assert_eq!(unavailable.to_string(), "Zoo API is unavailable");
+        assert!(format!("{:?}", unavailable).contains("<html>Bad Gateway</html>"));
+        let http = error(418, "<h1>Teapot</h1>");
+        assert_eq!(http.to_string(), "Zoo API request failed with 418 I'm a teapot");
+        let schema = parse_response(StatusCode::OK, None, "<html>").unwrap_err();
+        assert_eq!(schema.to_string(), "Unexpected response from the Zoo API");
+        assert!(error(503, "").is_transient());
+        assert!(!error(404, "").is_transient());
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let unavailable = ZooError::Unavailable(String::new());
+        assert!(retry_delay(&unavailable, 1, 3).unwrap() >= Duration::from_millis(500));
+        assert!(retry_delay(&unavailable, 3, 3).unwrap() >= Duration::from_secs(2));
+        assert!(retry_delay(&unavailable, 4, 3).is_none());
+        // Doesn't overflow with many retries
+        assert!(retry_delay(&unavailable, 40, 100).unwrap() <= MAX_BACKOFF.mul_f64(1.5));
+        assert!(retry_delay(&ZooError::NoZoo, 1, 3).is_none());
+        let limited = |secs| ZooError::RateLimited { retry_after: Some(Duration::from_secs(secs)) };
+        assert_eq!(retry_delay(&limited(5), 1, 3), Some(Duration::from_secs(5)));
+        assert_eq!(retry_delay(&limited(600), 1, 3), None);
+    }
+
+    #[test]
+    fn test_truncate_body() {
+        assert_eq!(truncate_body("short"), "short");
+        let html = format!("<html>{}</html>", "é".repeat(500));
+        let truncated = truncate_body(&html);
+        assert!(truncated.len() < MAX_ERROR_BODY + 30);
+        assert!(truncated.ends_with(&format!("… ({} bytes total)", html.len())));
+    }
+}